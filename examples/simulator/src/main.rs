@@ -0,0 +1,76 @@
+use std::{cell::RefCell, rc::Rc, thread, time::Duration};
+
+use embedded_graphics::{
+    geometry::Point,
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb888,
+    text::{Text, TextStyle},
+    Drawable,
+};
+use embedded_graphics_simulator::{OutputSettings, SimulatorDisplay, SimulatorEvent, Window};
+use profont::PROFONT_24_POINT;
+use weact_studio_epd::{
+    graphics::{Display213TriColor, DisplayRotation},
+    simulator::WeActStudio213SimulatedDriver,
+    TriColor,
+};
+
+type Driver = WeActStudio213SimulatedDriver<SimulatorDisplay<Rgb888>>;
+
+fn main() {
+    let rotation = DisplayRotation::Rotate90;
+    let mut driver = Driver::new(SimulatorDisplay::new(Driver::size(rotation)));
+
+    let window = Rc::new(RefCell::new(Window::new(
+        "WeAct Studio 2.13 inch",
+        &OutputSettings::default(),
+    )));
+    let animation_window = window.clone();
+    driver.set_animation(move |target| {
+        animation_window.borrow_mut().update(target);
+        thread::sleep(Duration::from_millis(150));
+    });
+
+    let mut display = Display213TriColor::new();
+    display.set_rotation(rotation);
+
+    let red = MonoTextStyle::new(&PROFONT_24_POINT, TriColor::Red);
+    let black = MonoTextStyle::new(&PROFONT_24_POINT, TriColor::Black);
+
+    Text::with_text_style("Hello", Point::new(8, 40), red, TextStyle::default())
+        .draw(&mut display)
+        .unwrap();
+    driver.full_update(&display).unwrap();
+
+    for count in 0.. {
+        display.clear(TriColor::White);
+        Text::with_text_style("Hello", Point::new(8, 40), red, TextStyle::default())
+            .draw(&mut display)
+            .unwrap();
+        Text::with_text_style(
+            &format!("Count: {count}"),
+            Point::new(8, 80),
+            black,
+            TextStyle::default(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        if count % 10 == 0 {
+            driver.full_update(&display).unwrap();
+        } else {
+            driver.fast_update(&display).unwrap();
+        }
+
+        let mut window = window.borrow_mut();
+        window.update(driver.target());
+        if window
+            .events()
+            .any(|event| matches!(event, SimulatorEvent::Quit))
+        {
+            break;
+        }
+        drop(window);
+        thread::sleep(Duration::from_secs(1));
+    }
+}
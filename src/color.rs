@@ -1,5 +1,7 @@
 #[cfg(feature = "graphics")]
-use embedded_graphics::pixelcolor::{BinaryColor, Rgb555, Rgb565, Rgb888, RgbColor};
+use embedded_graphics::pixelcolor::{
+    BinaryColor, Gray2, Gray4, Gray8, GrayColor, Rgb555, Rgb565, Rgb888, RgbColor,
+};
 use sealed::sealed;
 
 /// Color definition for B/W displays
@@ -23,92 +25,81 @@ impl From<BinaryColor> for Color {
     }
 }
 
-/// Conversion to RGB888 to use `Color` with `embedded-graphics-simulator`.
 #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
 #[cfg(feature = "graphics")]
-impl From<Color> for Rgb888 {
+impl From<Color> for BinaryColor {
     fn from(value: Color) -> Self {
         match value {
-            Color::Black => Rgb888::BLACK,
-            Color::White => Rgb888::WHITE,
+            Color::Black => BinaryColor::Off,
+            Color::White => BinaryColor::On,
         }
     }
 }
 
-/// Conversion from RGB888 to use `Color` with `embedded-graphics-simulator`.
-///
-/// Panics if the RGB value is not black or white.
-#[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
-#[cfg(feature = "graphics")]
-impl From<Rgb888> for Color {
-    fn from(value: Rgb888) -> Self {
-        match value {
-            Rgb888::BLACK => Color::Black,
-            Rgb888::WHITE => Color::White,
-            _ => panic!("RGB value must be black or white"),
+#[cfg(any(feature = "graphics", feature = "mock", test))]
+impl Color {
+    /// RGB value the color is shown as.
+    pub(crate) const fn rgb(self) -> [u8; 3] {
+        match self {
+            Color::Black => [0, 0, 0],
+            Color::White => [255, 255, 255],
         }
     }
 }
 
-/// Conversion from Rgb565 to use `Color` with tinybmp
+/// Conversion to RGB888 to use `Color` with `embedded-graphics-simulator`.
+#[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
 #[cfg(feature = "graphics")]
-impl From<Rgb565> for Color {
-    fn from(rgb: Rgb565) -> Self {
-        if rgb == RgbColor::BLACK {
-            Color::White
-        } else if rgb == RgbColor::WHITE {
-            Color::Black
-        } else {
-            // choose closest color
-            if (rgb.r() as u16 + rgb.g() as u16 + rgb.b() as u16) > 255 * 3 / 2 {
-                Color::Black
-            } else {
-                Color::White
-            }
-        }
+impl From<Color> for Rgb888 {
+    fn from(value: Color) -> Self {
+        let [r, g, b] = value.rgb();
+        Rgb888::new(r, g, b)
     }
 }
 
-/// Conversion to Rgb565 to use `Color` with tinybmp
+/// Conversion from RGB888, e.g. to draw images.
+///
+/// Colors with a perceived brightness of at least 50% are white, darker colors are black. Use
+/// [`graphics::dither`](crate::graphics::dither) for a better approximation of other colors.
+#[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
 #[cfg(feature = "graphics")]
-impl From<Color> for Rgb565 {
-    fn from(color: Color) -> Self {
-        match color {
-            Color::Black => Self::WHITE,
-            Color::White => Self::BLACK,
+impl From<Rgb888> for Color {
+    fn from(value: Rgb888) -> Self {
+        if is_bright(value) {
+            Color::White
+        } else {
+            Color::Black
         }
     }
 }
 
-/// Conversion from Rgb555 to use `Color` with tinybmp
+/// Implements the conversions between `Color` and other `embedded-graphics` colors through
+/// `Rgb888`.
 #[cfg(feature = "graphics")]
-impl From<Rgb555> for Color {
-    fn from(rgb: Rgb555) -> Self {
-        if rgb == RgbColor::BLACK {
-            Color::White
-        } else if rgb == RgbColor::WHITE {
-            Color::Black
-        } else {
-            // choose closest color
-            if (rgb.r() as u16 + rgb.g() as u16 + rgb.b() as u16) > 255 * 3 / 2 {
-                Color::Black
-            } else {
-                Color::White
+macro_rules! impl_color_conversions {
+    ($($type:ty),*) => {
+        $(
+            /// Conversion through RGB888, e.g. to draw images with `tinybmp`.
+            #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+            impl From<$type> for Color {
+                fn from(value: $type) -> Self {
+                    Rgb888::from(value).into()
+                }
             }
-        }
-    }
+
+            /// Conversion through RGB888.
+            #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+            impl From<Color> for $type {
+                fn from(value: Color) -> Self {
+                    Rgb888::from(value).into()
+                }
+            }
+        )*
+    };
 }
 
-/// Conversion to Rgb555 to use `Color` with tinybmp
 #[cfg(feature = "graphics")]
-impl From<Color> for Rgb555 {
-    fn from(color: Color) -> Self {
-        match color {
-            Color::Black => Self::WHITE,
-            Color::White => Self::BLACK,
-        }
-    }
-}
+impl_color_conversions!(Rgb565, Rgb555, Gray2, Gray4, Gray8);
 
 /// Color for tri-color displays
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
@@ -122,41 +113,173 @@ pub enum TriColor {
     Red,
 }
 
+#[cfg(any(feature = "graphics", feature = "mock", test))]
+impl TriColor {
+    /// RGB value the color is shown as.
+    pub(crate) const fn rgb(self) -> [u8; 3] {
+        match self {
+            TriColor::White => [255, 255, 255],
+            TriColor::Black => [0, 0, 0],
+            TriColor::Red => [255, 0, 0],
+        }
+    }
+}
+
 /// Conversion to RGB888 to use `TriColor` with `embedded-graphics-simulator`.
 #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
 #[cfg(feature = "graphics")]
 impl From<TriColor> for Rgb888 {
     fn from(val: TriColor) -> Self {
-        match val {
-            TriColor::White => Rgb888::WHITE,
-            TriColor::Black => Rgb888::BLACK,
-            TriColor::Red => Rgb888::RED,
-        }
+        let [r, g, b] = val.rgb();
+        Rgb888::new(r, g, b)
     }
 }
 
-/// Conversion from RGB888 to use `Color` with `embedded-graphics-simulator`.
+/// Conversion from RGB888, e.g. to draw images.
 ///
-/// Panics if the RGB value is not black, white or red.
+/// Reddish colors, with a red component of at least 128 that exceeds both the green and the blue
+/// component by at least 64, are red. Other colors with a perceived brightness of at least 50%
+/// are white, darker colors are black. The exact thresholds aren't part of the stable API. Use [`graphics::dither`](crate::graphics::dither) for a better
+/// approximation of other colors.
 #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
 #[cfg(feature = "graphics")]
 impl From<Rgb888> for TriColor {
     fn from(value: Rgb888) -> Self {
+        if is_reddish(value) {
+            TriColor::Red
+        } else if is_bright(value) {
+            TriColor::White
+        } else {
+            TriColor::Black
+        }
+    }
+}
+
+impl From<Color> for TriColor {
+    fn from(value: Color) -> Self {
         match value {
-            Rgb888::BLACK => TriColor::Black,
-            Rgb888::WHITE => TriColor::White,
-            Rgb888::RED => TriColor::Red,
-            _ => panic!("RGB value must be black, white, or red"),
+            Color::Black => TriColor::Black,
+            Color::White => TriColor::White,
+        }
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+#[cfg(feature = "graphics")]
+impl From<BinaryColor> for TriColor {
+    fn from(value: BinaryColor) -> Self {
+        Color::from(value).into()
+    }
+}
+
+/// Implements the conversions between `TriColor` and other `embedded-graphics` colors through
+/// `Rgb888`.
+#[cfg(feature = "graphics")]
+macro_rules! impl_tri_color_conversions {
+    ($($type:ty),*) => {
+        $(
+            /// Conversion through RGB888, e.g. to draw images with `tinybmp`.
+            #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+            impl From<$type> for TriColor {
+                fn from(value: $type) -> Self {
+                    Rgb888::from(value).into()
+                }
+            }
+
+            /// Conversion through RGB888.
+            #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+            impl From<TriColor> for $type {
+                fn from(value: TriColor) -> Self {
+                    Rgb888::from(value).into()
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "graphics")]
+impl_tri_color_conversions!(Rgb565, Rgb555, Gray2, Gray4, Gray8);
+
+/// Whether a color has a perceived brightness of at least 50%.
+#[cfg(feature = "graphics")]
+fn is_bright(color: Rgb888) -> bool {
+    Gray8::from(color).luma() >= 128
+}
+
+/// Whether a color is closer to red than to black, white or gray.
+///
+/// The red component must be at least half the maximum and exceed the green and blue components
+/// by a quarter of the range, so pinks and dark browns aren't red.
+#[cfg(feature = "graphics")]
+fn is_reddish(color: Rgb888) -> bool {
+    let (r, g, b) = (color.r(), color.g(), color.b());
+    r >= 128 && r.saturating_sub(g.max(b)) >= 64
+}
+
+/// Color for 4-level grayscale rendering on B/W displays.
+///
+/// The two bits of the gray level are stored in the B/W and the red buffer, which are shown
+/// together using a grayscale waveform.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GrayLevel {
+    /// Black color
+    Black,
+    /// Dark gray color
+    DarkGray,
+    /// Light gray color
+    LightGray,
+    /// White color
+    #[default]
+    White,
+}
+
+/// Conversion from the `embedded-graphics` gray color, e.g. to draw `ImageRaw<Gray2>` images.
+#[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+#[cfg(feature = "graphics")]
+impl From<Gray2> for GrayLevel {
+    fn from(value: Gray2) -> Self {
+        match value.luma() {
+            0 => GrayLevel::Black,
+            1 => GrayLevel::DarkGray,
+            2 => GrayLevel::LightGray,
+            _ => GrayLevel::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl GrayLevel {
+    /// RGB value the color is shown as.
+    pub(crate) const fn rgb(self) -> [u8; 3] {
+        match self {
+            GrayLevel::Black => [0x00, 0x00, 0x00],
+            GrayLevel::DarkGray => [0x55, 0x55, 0x55],
+            GrayLevel::LightGray => [0xAA, 0xAA, 0xAA],
+            GrayLevel::White => [0xFF, 0xFF, 0xFF],
         }
     }
 }
 
+/// Conversion to RGB888 to use `GrayLevel` with `embedded-graphics-simulator`.
+#[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+#[cfg(feature = "graphics")]
+impl From<GrayLevel> for Rgb888 {
+    fn from(value: GrayLevel) -> Self {
+        let [r, g, b] = value.rgb();
+        Rgb888::new(r, g, b)
+    }
+}
+
 /// Color trait for use in `Display`s.
 #[sealed]
 pub trait ColorType {
     /// Number of buffers used to represent this color type.
     const BUFFER_COUNT: usize;
 
+    /// Whether a set bit in the second buffer hides the bit in the first buffer, so drawing such a
+    /// color leaves the first buffer alone.
+    const SECOND_BUFFER_HIDES_FIRST: bool = false;
+
     /// Byte value of this color in the buffer.
     ///
     /// Useful for setting the full buffer to a single color.
@@ -172,6 +295,25 @@ pub trait ColorType {
     /// * `.0`: bit value in the first buffer
     /// * `.1`: bit value in the second buffer (only applicable to TriColor)
     fn bit_value(&self) -> (u8, u8);
+
+    /// Color represented by the given bit values in the buffers.
+    fn from_bit_values(bw_bit: u8, red_bit: u8) -> Self;
+
+    /// This color with black and white swapped.
+    ///
+    /// Red stays red and gray levels are mirrored.
+    fn inverted(&self) -> Self
+    where
+        Self: Sized,
+    {
+        let (bw_bit, red_bit) = self.bit_value();
+        let red_bit = if Self::BUFFER_COUNT == 2 && !Self::SECOND_BUFFER_HIDES_FIRST {
+            red_bit ^ 1
+        } else {
+            red_bit
+        };
+        Self::from_bit_values(bw_bit ^ 1, red_bit)
+    }
 }
 
 #[sealed]
@@ -191,11 +333,20 @@ impl ColorType for Color {
             Color::White => (0b1, 0),
         }
     }
+
+    fn from_bit_values(bw_bit: u8, _red_bit: u8) -> Self {
+        if bw_bit == 0 {
+            Color::Black
+        } else {
+            Color::White
+        }
+    }
 }
 
 #[sealed]
 impl ColorType for TriColor {
     const BUFFER_COUNT: usize = 2;
+    const SECOND_BUFFER_HIDES_FIRST: bool = true;
 
     fn byte_value(&self) -> (u8, u8) {
         // Red buffer value takes precedence over B/W buffer value.
@@ -214,4 +365,123 @@ impl ColorType for TriColor {
             TriColor::Red => (0, 0b1),
         }
     }
+
+    fn from_bit_values(bw_bit: u8, red_bit: u8) -> Self {
+        match (bw_bit, red_bit) {
+            (_, 1) => TriColor::Red,
+            (0, _) => TriColor::Black,
+            _ => TriColor::White,
+        }
+    }
+}
+
+#[sealed]
+impl ColorType for GrayLevel {
+    const BUFFER_COUNT: usize = 2;
+
+    fn byte_value(&self) -> (u8, u8) {
+        // The B/W buffer holds the high bit and the red buffer the low bit of the gray level.
+        match self {
+            GrayLevel::Black => (0x00, 0x00),
+            GrayLevel::DarkGray => (0x00, 0xFF),
+            GrayLevel::LightGray => (0xFF, 0x00),
+            GrayLevel::White => (0xFF, 0xFF),
+        }
+    }
+
+    fn bit_value(&self) -> (u8, u8) {
+        match self {
+            GrayLevel::Black => (0b0, 0b0),
+            GrayLevel::DarkGray => (0b0, 0b1),
+            GrayLevel::LightGray => (0b1, 0b0),
+            GrayLevel::White => (0b1, 0b1),
+        }
+    }
+
+    fn from_bit_values(bw_bit: u8, red_bit: u8) -> Self {
+        match (bw_bit, red_bit) {
+            (0, 0) => GrayLevel::Black,
+            (0, _) => GrayLevel::DarkGray,
+            (_, 0) => GrayLevel::LightGray,
+            _ => GrayLevel::White,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "graphics"))]
+mod tests {
+    use super::*;
+    use embedded_graphics::pixelcolor::Bgr888;
+
+    #[test]
+    fn rgb_conversions_keep_black_and_white() {
+        assert_eq!(Color::from(Rgb565::BLACK), Color::Black);
+        assert_eq!(Color::from(Rgb565::WHITE), Color::White);
+        assert_eq!(Color::from(Rgb555::BLACK), Color::Black);
+        assert_eq!(Color::from(Rgb555::WHITE), Color::White);
+        assert_eq!(Color::from(Rgb888::BLACK), Color::Black);
+        assert_eq!(Color::from(Rgb888::WHITE), Color::White);
+
+        assert_eq!(Rgb565::from(Color::Black), Rgb565::BLACK);
+        assert_eq!(Rgb565::from(Color::White), Rgb565::WHITE);
+        assert_eq!(Rgb555::from(Color::Black), Rgb555::BLACK);
+        assert_eq!(Rgb555::from(Color::White), Rgb555::WHITE);
+    }
+
+    #[test]
+    fn gray_and_binary_conversions_keep_black_and_white() {
+        assert_eq!(Color::from(Gray8::BLACK), Color::Black);
+        assert_eq!(Color::from(Gray8::WHITE), Color::White);
+        assert_eq!(Color::from(Gray4::new(7)), Color::Black);
+        assert_eq!(Color::from(Gray4::new(8)), Color::White);
+        assert_eq!(Color::from(Gray2::new(1)), Color::Black);
+        assert_eq!(Color::from(Gray2::new(2)), Color::White);
+        assert_eq!(Color::from(BinaryColor::Off), Color::Black);
+        assert_eq!(Color::from(BinaryColor::On), Color::White);
+
+        assert_eq!(Gray8::from(Color::Black), Gray8::BLACK);
+        assert_eq!(Gray8::from(Color::White), Gray8::WHITE);
+        assert_eq!(BinaryColor::from(Color::Black), BinaryColor::Off);
+        assert_eq!(BinaryColor::from(Color::White), BinaryColor::On);
+    }
+
+    #[test]
+    fn other_colors_use_the_brightness() {
+        assert_eq!(Color::from(Rgb888::new(100, 100, 100)), Color::Black);
+        assert_eq!(Color::from(Rgb888::new(160, 160, 160)), Color::White);
+        assert_eq!(Color::from(Rgb565::YELLOW), Color::White);
+        assert_eq!(Color::from(Rgb565::BLUE), Color::Black);
+        assert_eq!(Color::from(Rgb888::from(Bgr888::CYAN)), Color::White);
+    }
+
+    #[test]
+    fn tri_color_conversions_map_reddish_colors_to_red() {
+        assert_eq!(TriColor::from(Rgb888::RED), TriColor::Red);
+        assert_eq!(TriColor::from(Rgb565::RED), TriColor::Red);
+        assert_eq!(TriColor::from(Rgb555::new(20, 4, 4)), TriColor::Red);
+        assert_eq!(TriColor::from(Rgb888::new(140, 30, 40)), TriColor::Red);
+
+        assert_eq!(TriColor::from(Rgb888::new(255, 200, 200)), TriColor::White);
+        assert_eq!(TriColor::from(Rgb888::new(100, 60, 60)), TriColor::Black);
+        assert_eq!(TriColor::from(Rgb565::BLACK), TriColor::Black);
+        assert_eq!(TriColor::from(Rgb565::WHITE), TriColor::White);
+        assert_eq!(TriColor::from(Gray8::new(200)), TriColor::White);
+        assert_eq!(TriColor::from(BinaryColor::Off), TriColor::Black);
+
+        assert_eq!(Rgb565::from(TriColor::Red), Rgb565::RED);
+        assert_eq!(Rgb555::from(TriColor::White), Rgb555::WHITE);
+        assert_eq!(Rgb888::from(TriColor::Black), Rgb888::BLACK);
+    }
+
+    #[test]
+    fn inverted_colors() {
+        assert_eq!(Color::Black.inverted(), Color::White);
+        assert_eq!(Color::White.inverted(), Color::Black);
+        assert_eq!(TriColor::Black.inverted(), TriColor::White);
+        assert_eq!(TriColor::White.inverted(), TriColor::Black);
+        assert_eq!(TriColor::Red.inverted(), TriColor::Red);
+        assert_eq!(GrayLevel::Black.inverted(), GrayLevel::White);
+        assert_eq!(GrayLevel::DarkGray.inverted(), GrayLevel::LightGray);
+        assert_eq!(GrayLevel::LightGray.inverted(), GrayLevel::DarkGray);
+    }
 }
@@ -1,11 +1,14 @@
 #[allow(unused)]
 mod commands {
     pub const DRIVER_CONTROL: u8 = 0x01;
+    pub const GATE_VOLTAGE: u8 = 0x03;
+    pub const SOURCE_VOLTAGE: u8 = 0x04;
     pub const SET_SOFTSTART: u8 = 0x0C;
     pub const DEEP_SLEEP: u8 = 0x10;
     pub const DATA_ENTRY_MODE: u8 = 0x11;
     pub const SW_RESET: u8 = 0x12;
     pub const TEMP_CONTROL: u8 = 0x18;
+    pub const WRITE_TEMPERATURE: u8 = 0x1A;
     pub const MASTER_ACTIVATE: u8 = 0x20;
     pub const DISPLAY_UPDATE_CONTROL: u8 = 0x21;
     pub const UPDATE_DISPLAY_CTRL2: u8 = 0x22;
@@ -14,6 +17,7 @@ mod commands {
     pub const WRITE_VCOM: u8 = 0x2C;
     pub const WRITE_LUT: u8 = 0x32;
     pub const BORDER_WAVEFORM_CONTROL: u8 = 0x3C;
+    pub const END_OPTION: u8 = 0x3F;
     pub const SET_RAMXPOS: u8 = 0x44;
     pub const SET_RAMYPOS: u8 = 0x45;
     pub const SET_RAMX_COUNTER: u8 = 0x4E;
@@ -0,0 +1,178 @@
+use crate::{command, flag, lut};
+
+/// A list of commands and the data belonging to them.
+pub type CommandSequence = &'static [(u8, &'static [u8])];
+
+/// Display controller chip used by a display.
+///
+/// The WeAct Studio modules use closely related Solomon Systech controllers that share most of
+/// their command set. This trait captures the parts that differ between them. The panel
+/// geometry is described by the [`Panel`](crate::Panel) trait, whose constants are used as the
+/// const generics of [`DisplayDriver`](crate::DisplayDriver) so that display buffers can be
+/// checked against it at compile time.
+///
+/// Implement this trait to use the driver with displays that aren't supported out of the box,
+/// for example clones that use a different controller configuration.
+///
+/// ```
+/// use weact_studio_epd::{Color, CommandSequence, Controller, DisplayDriver, Panel};
+///
+/// struct MyController;
+///
+/// impl Controller for MyController {
+///     const MAX_WIDTH: u32 = 176;
+///     const MAX_HEIGHT: u32 = 296;
+///     const RAM_X_ADDRESS_BYTES: usize = 1;
+///     const RAM_Y_ADDRESS_BYTES: usize = 2;
+///     const DISPLAY_UPDATE_CONTROL: [u8; 2] = [0x00, 0x80];
+///     // Use the fast refresh waveform stored in OTP.
+///     const FAST_REFRESH_LUT: Option<&'static [u8; 153]> = None;
+///     const FAST_REFRESH_UPDATE_CONTROL: u8 = 0xFF;
+/// }
+///
+/// struct MyPanel;
+///
+/// impl Panel for MyPanel {
+///     const WIDTH: u32 = 128;
+///     const VISIBLE_WIDTH: u32 = 128;
+///     const HEIGHT: u32 = 296;
+///     type Controller = MyController;
+/// }
+///
+/// type MyDisplayDriver<DI, BSY, RST, DELAY> = DisplayDriver<
+///     DI,
+///     BSY,
+///     RST,
+///     DELAY,
+///     { MyPanel::WIDTH },
+///     { MyPanel::VISIBLE_WIDTH },
+///     { MyPanel::HEIGHT },
+///     Color,
+///     <MyPanel as Panel>::Controller,
+/// >;
+/// ```
+pub trait Controller {
+    /// Maximum display width (number of sources) supported by the controller.
+    const MAX_WIDTH: u32;
+
+    /// Maximum display height (number of gates) supported by the controller.
+    const MAX_HEIGHT: u32;
+
+    /// Width in bytes of the X addresses in the RAM window and RAM counter commands.
+    const RAM_X_ADDRESS_BYTES: usize;
+
+    /// Width in bytes of the Y addresses in the RAM window and RAM counter commands.
+    const RAM_Y_ADDRESS_BYTES: usize;
+
+    /// Commands sent during initialization after the driver output control, data entry mode and
+    /// display update control have been set.
+    ///
+    /// Defaults to setting the border waveform and selecting the internal temperature sensor.
+    const INIT_SEQUENCE: CommandSequence = &[
+        (
+            command::BORDER_WAVEFORM_CONTROL,
+            &[flag::BORDER_WAVEFORM_FOLLOW_LUT | flag::BORDER_WAVEFORM_LUT1],
+        ),
+        (command::TEMP_CONTROL, &[flag::INTERNAL_TEMP_SENSOR]),
+    ];
+
+    /// Data for the "Display Update Control 1" (`0x21`) command sent during initialization.
+    const DISPLAY_UPDATE_CONTROL: [u8; 2];
+
+    /// Value for the "Display Update Control 2" (`0x22`) command used for full refreshes.
+    ///
+    /// Defaults to loading the temperature and the waveform from OTP and refreshing in display
+    /// mode 1.
+    const FULL_REFRESH_UPDATE_CONTROL: u8 = flag::DISPLAY_MODE_1;
+
+    /// Waveform written to the LUT register before the first fast refresh, or `None` to use the
+    /// fast refresh waveform stored in OTP.
+    const FAST_REFRESH_LUT: Option<&'static [u8; 153]>;
+
+    /// Commands sent after the fast refresh waveform to set the driving voltages that belong to
+    /// it.
+    ///
+    /// These are also sent when a custom waveform replaces [`Self::FAST_REFRESH_LUT`]. Defaults
+    /// to no commands.
+    const FAST_REFRESH_VOLTAGES: CommandSequence = &[];
+
+    /// Value for the "Display Update Control 2" (`0x22`) command used for fast refreshes.
+    const FAST_REFRESH_UPDATE_CONTROL: u8;
+
+    /// Value for the "Display Update Control 2" (`0x22`) command used for fast refreshes with a
+    /// custom waveform in the LUT register.
+    ///
+    /// This must not load a waveform from OTP. Defaults to display mode 2 using the LUT register.
+    const FAST_REFRESH_REGISTER_LUT_UPDATE_CONTROL: u8 = flag::DISPLAY_MODE_2_REGISTER_LUT;
+
+    /// Commands sent before a grayscale refresh to load the 4-level grayscale waveform, or `None`
+    /// if the controller doesn't support grayscale.
+    ///
+    /// Defaults to `None`.
+    const GRAY_REFRESH_SETUP: Option<CommandSequence> = None;
+
+    /// Data for the "Driver Output Control" (`0x01`) command for a display with `height` gate
+    /// lines.
+    fn driver_control(height: u32) -> [u8; 3] {
+        let [mux_low, mux_high, ..] = (height - 1).to_le_bytes();
+        [mux_low, mux_high, 0x00]
+    }
+}
+
+/// SSD1680 controller used by the 2.13 and 2.9 inch displays.
+pub struct Ssd1680;
+
+impl Controller for Ssd1680 {
+    const MAX_WIDTH: u32 = 176;
+    const MAX_HEIGHT: u32 = 296;
+    const RAM_X_ADDRESS_BYTES: usize = 1;
+    const RAM_Y_ADDRESS_BYTES: usize = 2;
+    // Only sources S8 to S167 are connected.
+    const DISPLAY_UPDATE_CONTROL: [u8; 2] = [0x00, 0x80];
+    const FAST_REFRESH_LUT: Option<&'static [u8; 153]> = Some(&lut::LUT_PARTIAL_UPDATE);
+    const FAST_REFRESH_UPDATE_CONTROL: u8 = flag::UNDOCUMENTED;
+    const FAST_REFRESH_REGISTER_LUT_UPDATE_CONTROL: u8 = flag::UNDOCUMENTED;
+    const GRAY_REFRESH_SETUP: Option<CommandSequence> = Some(&[
+        (command::WRITE_LUT, &lut::LUT_GRAY2),
+        (command::END_OPTION, &[0x22]),
+        (command::GATE_VOLTAGE, &[0x17]),
+        (command::SOURCE_VOLTAGE, &[0x41, 0xAE, 0x32]),
+        (command::WRITE_VCOM, &[0x28]),
+    ]);
+}
+
+/// SSD1681 controller used by the 1.54 inch display.
+pub struct Ssd1681;
+
+impl Controller for Ssd1681 {
+    const MAX_WIDTH: u32 = 200;
+    const MAX_HEIGHT: u32 = 200;
+    const RAM_X_ADDRESS_BYTES: usize = 1;
+    const RAM_Y_ADDRESS_BYTES: usize = 2;
+    // All 200 sources are connected so the source output mode stays at its reset value.
+    const DISPLAY_UPDATE_CONTROL: [u8; 2] = [0x00, 0x00];
+    // The SSD1681 needs the driving voltages that belong to the waveform in addition to the
+    // LUT itself.
+    const FAST_REFRESH_LUT: Option<&'static [u8; 153]> = Some(&lut::LUT_PARTIAL_UPDATE_SSD1681);
+    const FAST_REFRESH_VOLTAGES: CommandSequence = &[
+        (command::END_OPTION, &[0x02]),
+        (command::GATE_VOLTAGE, &[0x17]),
+        (command::SOURCE_VOLTAGE, &[0x41, 0xB0, 0x32]),
+        (command::WRITE_VCOM, &[0x28]),
+    ];
+    const FAST_REFRESH_UPDATE_CONTROL: u8 = flag::DISPLAY_MODE_2_REGISTER_LUT;
+}
+
+/// SSD1683 controller used by the 4.2 inch display.
+pub struct Ssd1683;
+
+impl Controller for Ssd1683 {
+    const MAX_WIDTH: u32 = 400;
+    const MAX_HEIGHT: u32 = 300;
+    const RAM_X_ADDRESS_BYTES: usize = 1;
+    const RAM_Y_ADDRESS_BYTES: usize = 2;
+    const DISPLAY_UPDATE_CONTROL: [u8; 2] = [0x00, 0x00];
+    // The SSD1683 has a usable fast refresh waveform in OTP so no custom LUT is needed.
+    const FAST_REFRESH_LUT: Option<&'static [u8; 153]> = None;
+    const FAST_REFRESH_UPDATE_CONTROL: u8 = flag::DISPLAY_MODE_2;
+}
@@ -1,4 +1,10 @@
 use core::iter;
+#[cfg(not(feature = "blocking"))]
+use core::{
+    future::{poll_fn, Future},
+    pin::pin,
+    task::Poll,
+};
 
 #[cfg(not(feature = "blocking"))]
 use display_interface::AsyncWriteOnlyDataCommand;
@@ -11,27 +17,119 @@ use embedded_hal::delay::DelayNs;
 use embedded_hal_async::{delay::DelayNs, digital::Wait};
 
 use display_interface::DataFormat;
+#[cfg(feature = "graphics")]
+use embedded_graphics::primitives::Rectangle;
 use embedded_hal::digital::{InputPin, OutputPin};
 
-#[cfg(feature = "graphics")]
-use crate::graphics::Display;
 use crate::{
     color::{self, ColorType},
-    command, flag, lut, Color, Result, TriColor,
+    command, flag,
+    lut::{Lut, TemperatureBand},
+    Color, Controller, Error, Panel, Result, Ssd1680, TriColor, WeActStudio154, WeActStudio213,
+    WeActStudio290, WeActStudio420,
 };
+#[cfg(feature = "graphics")]
+use crate::{
+    graphics::{Display, Viewport},
+    GrayLevel,
+};
+
+/// Display driver for the given [`Panel`] and color.
+macro_rules! panel_driver {
+    ($panel:ty, $color:ty) => {
+        DisplayDriver<
+            DI,
+            BSY,
+            RST,
+            DELAY,
+            { <$panel>::WIDTH },
+            { <$panel>::VISIBLE_WIDTH },
+            { <$panel>::HEIGHT },
+            $color,
+            <$panel as Panel>::Controller,
+        >
+    };
+}
 
+/// Display driver for the WeAct Studio 1.54 inch B/W display.
+pub type WeActStudio154BlackWhiteDriver<DI, BSY, RST, DELAY> = panel_driver!(WeActStudio154, Color);
 /// Display driver for the WeAct Studio 2.9 inch B/W display.
-pub type WeActStudio290BlackWhiteDriver<DI, BSY, RST, DELAY> =
-    DisplayDriver<DI, BSY, RST, DELAY, 128, 128, 296, Color>;
+pub type WeActStudio290BlackWhiteDriver<DI, BSY, RST, DELAY> = panel_driver!(WeActStudio290, Color);
 /// Display driver for the WeAct Studio 2.9 inch Tri-Color display.
 pub type WeActStudio290TriColorDriver<DI, BSY, RST, DELAY> =
-    DisplayDriver<DI, BSY, RST, DELAY, 128, 128, 296, TriColor>;
+    panel_driver!(WeActStudio290, TriColor);
 /// Display driver for the WeAct Studio 2.13 inch B/W display.
-pub type WeActStudio213BlackWhiteDriver<DI, BSY, RST, DELAY> =
-    DisplayDriver<DI, BSY, RST, DELAY, 128, 122, 250, Color>;
+pub type WeActStudio213BlackWhiteDriver<DI, BSY, RST, DELAY> = panel_driver!(WeActStudio213, Color);
 /// Display driver for the WeAct Studio 2.13 inch Tri-Color display.
 pub type WeActStudio213TriColorDriver<DI, BSY, RST, DELAY> =
-    DisplayDriver<DI, BSY, RST, DELAY, 128, 122, 250, TriColor>;
+    panel_driver!(WeActStudio213, TriColor);
+/// Display driver for the WeAct Studio 4.2 inch B/W display.
+pub type WeActStudio420BlackWhiteDriver<DI, BSY, RST, DELAY> = panel_driver!(WeActStudio420, Color);
+
+/// Policy for replacing fast refreshes with full refreshes to clear ghosting.
+///
+/// Fast refreshes leave traces of previous images behind that build up over time. A full refresh
+/// clears them. When both limits are set a full refresh is done as soon as either one is reached.
+///
+/// The default policy never replaces fast refreshes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefreshPolicy {
+    /// Do a full refresh instead of a fast refresh after this many fast refreshes since the last
+    /// full refresh.
+    pub full_refresh_every: Option<u32>,
+    /// Do a full refresh instead of a fast refresh when at least this many milliseconds have
+    /// passed since the last full refresh.
+    ///
+    /// The driver has no clock, use [`DisplayDriver::advance_time`] to tell it how much time has
+    /// passed.
+    pub full_refresh_after_ms: Option<u32>,
+}
+
+/// Orientation in which buffers are written to the display RAM.
+///
+/// The orientation is applied by the controller by changing the direction in which its RAM
+/// address counters move, so buffers don't have to be transformed in software. Positions of
+/// partial updates are in the coordinates of the transformed image.
+///
+/// The controller always stores 8 horizontally adjacent pixels in a byte, so rotations by 90
+/// degrees, which would need the bits of 8 bytes to be transposed, aren't possible in hardware.
+/// Mirroring horizontally reverses the bits of every byte on the fly and mirrors the whole
+/// `WIDTH` columns of the buffer. On displays with a `VISIBLE_WIDTH` smaller than `WIDTH`, like
+/// the 2.13 inch displays, this would move the image by `WIDTH - VISIBLE_WIDTH` columns, which
+/// isn't a whole number of bytes, so horizontal mirroring isn't supported there.
+/// [`Orientation::MirrorVertical`] is supported on all displays.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Orientation {
+    /// Buffers are shown as they are.
+    #[default]
+    Normal,
+    /// Mirror buffers horizontally.
+    MirrorHorizontal,
+    /// Mirror buffers vertically.
+    MirrorVertical,
+    /// Rotate buffers by 180 degrees.
+    Rotate180,
+}
+
+impl Orientation {
+    fn mirrors_x(self) -> bool {
+        matches!(self, Orientation::MirrorHorizontal | Orientation::Rotate180)
+    }
+
+    fn mirrors_y(self) -> bool {
+        matches!(self, Orientation::MirrorVertical | Orientation::Rotate180)
+    }
+
+    /// Value for the "Data Entry Mode" (`0x11`) command.
+    fn data_entry_mode(self) -> u8 {
+        match self {
+            Orientation::Normal => flag::DATA_ENTRY_INCRY_INCRX,
+            Orientation::MirrorHorizontal => flag::DATA_ENTRY_INCRY_DECRX,
+            Orientation::MirrorVertical => flag::DATA_ENTRY_DECRY_INCRX,
+            Orientation::Rotate180 => flag::DATA_ENTRY_DECRY_DECRX,
+        }
+    }
+}
 
 /// The main driver struct that manages the communication with the display.
 ///
@@ -45,15 +143,30 @@ pub struct DisplayDriver<
     const VISIBLE_WIDTH: u32,
     const HEIGHT: u32,
     C,
+    CTRL = Ssd1680,
 > {
     _color: core::marker::PhantomData<C>,
+    _controller: core::marker::PhantomData<CTRL>,
     interface: DI,
     busy: BSY,
     reset: RST,
     delay: DELAY,
     // State
+    busy_timeout_ms: Option<u32>,
+    orientation: Orientation,
+    gate_scan_reversed: bool,
+    refresh_policy: RefreshPolicy,
+    full_refresh_count: u32,
+    fast_refresh_count: u32,
+    fast_refreshes_since_full_refresh: u32,
+    ms_since_full_refresh: u32,
     using_partial_mode: bool,
+    bypassing_red_ram: bool,
     initial_full_refresh_done: bool,
+    fast_refresh_lut: Option<Lut>,
+    full_refresh_lut: Option<Lut>,
+    fast_refresh_lut_bands: Option<&'static [TemperatureBand]>,
+    temperature: Option<i8>,
 }
 
 #[maybe_async_cfg::maybe(
@@ -67,72 +180,355 @@ pub struct DisplayDriver<
     ),
     async(not(feature = "blocking"), keep_self)
 )]
-impl<DI, BSY, RST, DELAY, const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, C>
-    DisplayDriver<DI, BSY, RST, DELAY, WIDTH, VISIBLE_WIDTH, HEIGHT, C>
+impl<
+        DI,
+        BSY,
+        RST,
+        DELAY,
+        const WIDTH: u32,
+        const VISIBLE_WIDTH: u32,
+        const HEIGHT: u32,
+        C,
+        CTRL,
+    > DisplayDriver<DI, BSY, RST, DELAY, WIDTH, VISIBLE_WIDTH, HEIGHT, C, CTRL>
 where
     DI: AsyncWriteOnlyDataCommand,
     BSY: InputPin + Wait,
     RST: OutputPin,
     DELAY: DelayNs,
     C: ColorType,
+    CTRL: Controller,
 {
     const RESET_DELAY_MS: u32 = 50;
 
+    /// Fails compilation if the display dimensions aren't supported by the controller.
+    const DIMENSIONS_CHECK: () = {
+        assert!(
+            WIDTH <= CTRL::MAX_WIDTH && HEIGHT <= CTRL::MAX_HEIGHT,
+            "display is larger than the controller supports"
+        );
+        assert!(
+            matches!(
+                ((WIDTH - 1) >> 3).checked_shr(8 * CTRL::RAM_X_ADDRESS_BYTES as u32),
+                None | Some(0)
+            ),
+            "display is too wide for the controller's RAM X address"
+        );
+        assert!(
+            matches!(
+                (HEIGHT - 1).checked_shr(8 * CTRL::RAM_Y_ADDRESS_BYTES as u32),
+                None | Some(0)
+            ),
+            "display is too high for the controller's RAM Y address"
+        );
+    };
+
     /// Create a new display driver.
     ///
     /// Use [`Self::init`] to initialize the display.
     pub fn new(interface: DI, busy: BSY, reset: RST, delay: DELAY) -> Self {
+        let () = Self::DIMENSIONS_CHECK;
         Self {
             _color: core::marker::PhantomData,
+            _controller: core::marker::PhantomData,
             interface,
             busy,
             reset,
             delay,
+            busy_timeout_ms: None,
+            orientation: Orientation::Normal,
+            gate_scan_reversed: false,
+            refresh_policy: RefreshPolicy::default(),
+            full_refresh_count: 0,
+            fast_refresh_count: 0,
+            fast_refreshes_since_full_refresh: 0,
+            ms_since_full_refresh: 0,
             using_partial_mode: false,
+            bypassing_red_ram: false,
             initial_full_refresh_done: false,
+            fast_refresh_lut: None,
+            full_refresh_lut: None,
+            fast_refresh_lut_bands: None,
+            temperature: None,
+        }
+    }
+
+    /// Set the maximum time to wait for the display to become idle.
+    ///
+    /// Operations that wait for the busy pin return [`Error::BusyTimeout`] when the display stays
+    /// busy for longer than this, for example because it is disconnected. A full refresh of a
+    /// tri-color display can take around 15 seconds so make sure the timeout is long enough.
+    ///
+    /// By default there is no timeout.
+    pub fn set_busy_timeout(&mut self, timeout_ms: Option<u32>) {
+        self.busy_timeout_ms = timeout_ms;
+    }
+
+    /// Get the orientation in which buffers are written to the display.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Set the orientation in which buffers are written to the display.
+    ///
+    /// Only affects buffers written after this call, the image that is currently on the display
+    /// or in its RAM isn't transformed.
+    ///
+    /// Returns [`Error::Unsupported`] for orientations that mirror horizontally on displays with a
+    /// `VISIBLE_WIDTH` smaller than `WIDTH`, see [`Orientation`].
+    pub async fn set_orientation(&mut self, orientation: Orientation) -> Result<()> {
+        if orientation.mirrors_x() && VISIBLE_WIDTH != WIDTH {
+            return Err(Error::Unsupported);
+        }
+        self.orientation = orientation;
+        self.command_with_data(command::DATA_ENTRY_MODE, &[orientation.data_entry_mode()])
+            .await
+    }
+
+    /// Whether the gate scan direction is reversed.
+    pub fn gate_scan_reversed(&self) -> bool {
+        self.gate_scan_reversed
+    }
+
+    /// Reverse the order in which the gates are scanned, which mirrors the displayed image
+    /// vertically without changing the RAM.
+    ///
+    /// Unlike [`Self::set_orientation`] this also affects the image that is currently in the
+    /// display RAM, starting with the next refresh.
+    pub async fn set_gate_scan_reversed(&mut self, reversed: bool) -> Result<()> {
+        self.gate_scan_reversed = reversed;
+        self.command_with_data(command::DRIVER_CONTROL, &self.driver_control())
+            .await
+    }
+
+    /// Data for the "Driver Output Control" command.
+    fn driver_control(&self) -> [u8; 3] {
+        let mut data = CTRL::driver_control(HEIGHT);
+        if self.gate_scan_reversed {
+            data[2] |= flag::GATE_SCAN_REVERSED;
+        }
+        data
+    }
+
+    /// Get the current refresh policy.
+    pub fn refresh_policy(&self) -> RefreshPolicy {
+        self.refresh_policy
+    }
+
+    /// Set the policy for replacing fast refreshes with full refreshes.
+    pub fn set_refresh_policy(&mut self, policy: RefreshPolicy) {
+        self.refresh_policy = policy;
+    }
+
+    /// Tell the driver that `elapsed_ms` milliseconds have passed.
+    ///
+    /// Used for [`RefreshPolicy::full_refresh_after_ms`].
+    pub fn advance_time(&mut self, elapsed_ms: u32) {
+        self.ms_since_full_refresh = self.ms_since_full_refresh.saturating_add(elapsed_ms);
+    }
+
+    /// Number of full refreshes done since the driver was created.
+    pub fn full_refresh_count(&self) -> u32 {
+        self.full_refresh_count
+    }
+
+    /// Number of fast refreshes done since the driver was created.
+    pub fn fast_refresh_count(&self) -> u32 {
+        self.fast_refresh_count
+    }
+
+    /// Number of fast refreshes done since the last full refresh.
+    pub fn fast_refreshes_since_full_refresh(&self) -> u32 {
+        self.fast_refreshes_since_full_refresh
+    }
+
+    /// Time in milliseconds since the last full refresh, as reported with [`Self::advance_time`].
+    pub fn ms_since_full_refresh(&self) -> u32 {
+        self.ms_since_full_refresh
+    }
+
+    /// Whether the refresh policy requires the next fast refresh to be a full refresh.
+    pub fn full_refresh_due(&self) -> bool {
+        let policy = self.refresh_policy;
+        policy
+            .full_refresh_every
+            .is_some_and(|n| self.fast_refreshes_since_full_refresh >= n)
+            || policy
+                .full_refresh_after_ms
+                .is_some_and(|ms| self.ms_since_full_refresh >= ms)
+    }
+
+    /// Get the custom waveform used for fast refreshes, if any.
+    pub fn fast_refresh_lut(&self) -> Option<&Lut> {
+        self.fast_refresh_lut.as_ref()
+    }
+
+    /// Use a custom waveform for fast refreshes.
+    ///
+    /// The waveform replaces the controller's built-in fast refresh LUT and is loaded before the
+    /// next fast refresh. Other settings of the fast refresh, like the driving voltages, are kept.
+    /// Returns [`Error::InvalidLut`] if the waveform isn't [valid](Lut::validate).
+    ///
+    /// This replaces waveforms set with [`Self::set_fast_refresh_lut_bands`].
+    pub fn set_fast_refresh_lut(&mut self, lut: Lut) -> Result<()> {
+        lut.validate()?;
+        self.fast_refresh_lut_bands = None;
+        self.fast_refresh_lut = Some(lut);
+        self.using_partial_mode = false;
+        Ok(())
+    }
+
+    /// Use a custom waveform for fast refreshes depending on the temperature.
+    ///
+    /// The waveform of the band that matches the temperature set with [`Self::set_temperature`]
+    /// is used, see [`TemperatureBand`]. While the internal temperature sensor is used the
+    /// controller's built-in fast refresh waveform is used instead, as the driver can't read the
+    /// sensor. Returns [`Error::InvalidLut`] if the bands aren't
+    /// [valid](TemperatureBand::validate).
+    pub fn set_fast_refresh_lut_bands(&mut self, bands: &'static [TemperatureBand]) -> Result<()> {
+        TemperatureBand::validate(bands)?;
+        self.fast_refresh_lut_bands = Some(bands);
+        self.select_fast_refresh_lut();
+        Ok(())
+    }
+
+    /// Pick the fast refresh waveform for the current temperature from the temperature bands.
+    fn select_fast_refresh_lut(&mut self) {
+        let Some(bands) = self.fast_refresh_lut_bands else {
+            return;
+        };
+        let lut = self
+            .temperature
+            .and_then(|temperature| TemperatureBand::select(bands, temperature))
+            .copied();
+        if lut != self.fast_refresh_lut {
+            self.fast_refresh_lut = lut;
+            self.using_partial_mode = false;
+        }
+    }
+
+    /// Get the custom waveform used for full refreshes, if any.
+    pub fn full_refresh_lut(&self) -> Option<&Lut> {
+        self.full_refresh_lut.as_ref()
+    }
+
+    /// Use a custom waveform for full refreshes instead of the one from the controller's OTP.
+    ///
+    /// The waveform is loaded before every full refresh, because fast refreshes replace it.
+    /// Returns [`Error::InvalidLut`] if the waveform isn't [valid](Lut::validate).
+    pub fn set_full_refresh_lut(&mut self, lut: Lut) -> Result<()> {
+        lut.validate()?;
+        self.full_refresh_lut = Some(lut);
+        Ok(())
+    }
+
+    /// Switch back to the built-in waveforms: the OTP waveform for full refreshes and the
+    /// controller's fast refresh waveform.
+    pub fn use_default_luts(&mut self) {
+        self.fast_refresh_lut_bands = None;
+        if self.fast_refresh_lut.take().is_some() {
+            self.using_partial_mode = false;
+        }
+        self.full_refresh_lut = None;
+    }
+
+    /// Get the temperature in degrees Celsius set with [`Self::set_temperature`].
+    ///
+    /// This echoes the last value passed to [`Self::set_temperature`], or `None` while the
+    /// controller uses its internal temperature sensor.
+    pub fn temperature(&self) -> Option<i8> {
+        self.temperature
+    }
+
+    /// Use an external temperature reading in degrees Celsius instead of the internal sensor.
+    ///
+    /// The controller selects the OTP waveform for full refreshes based on the temperature, and
+    /// waveforms set with [`Self::set_fast_refresh_lut_bands`] are picked by it. Refreshes no
+    /// longer load the temperature from the internal sensor until
+    /// [`Self::use_internal_temperature_sensor`] is called. The value is written again by
+    /// [`Self::init`] and [`Self::wake_up`].
+    pub async fn set_temperature(&mut self, temperature: i8) -> Result<()> {
+        self.temperature = Some(temperature);
+        self.select_fast_refresh_lut();
+        self.write_temperature(temperature).await
+    }
+
+    /// Switch back to the controller's internal temperature sensor.
+    pub async fn use_internal_temperature_sensor(&mut self) -> Result<()> {
+        self.temperature = None;
+        self.select_fast_refresh_lut();
+        self.command_with_data(command::TEMP_CONTROL, &[flag::INTERNAL_TEMP_SENSOR])
+            .await
+    }
+
+    async fn write_temperature(&mut self, temperature: i8) -> Result<()> {
+        // The register holds a 12 bit two's complement value in 1/16 degrees Celsius.
+        self.command_with_data(command::WRITE_TEMPERATURE, &[temperature as u8, 0x00])
+            .await
+    }
+
+    /// Value for the "Display Update Control 2" command that doesn't overwrite an external
+    /// temperature with the reading of the internal sensor.
+    fn update_control(&self, value: u8) -> u8 {
+        if self.temperature.is_some() {
+            value & !flag::LOAD_TEMPERATURE
+        } else {
+            value
         }
     }
 
     /// Initialize the display
     pub async fn init(&mut self) -> Result<()> {
-        self.hw_reset().await;
+        self.hw_reset().await?;
         self.command(command::SW_RESET).await?;
         self.delay.delay_ms(10).await;
-        self.wait_until_idle().await;
+        self.wait_until_idle().await?;
+        self.restore_registers().await?;
+        self.use_full_frame().await?;
+        self.wait_until_idle().await?;
+        Ok(())
+    }
+
+    /// Write the driver's settings to the registers after a reset set them back to their
+    /// defaults.
+    async fn restore_registers(&mut self) -> Result<()> {
+        // The reset drops the fast refresh waveform and the red RAM bypass.
+        self.using_partial_mode = false;
+        self.bypassing_red_ram = false;
+        self.command_with_data(command::DRIVER_CONTROL, &self.driver_control())
+            .await?;
         self.command_with_data(
-            command::DRIVER_CONTROL,
-            &[(HEIGHT - 1) as u8, ((HEIGHT - 1) >> 8) as u8, 0x00],
+            command::DATA_ENTRY_MODE,
+            &[self.orientation.data_entry_mode()],
         )
         .await?;
-        self.command_with_data(command::DATA_ENTRY_MODE, &[flag::DATA_ENTRY_INCRY_INCRX])
-            .await?;
         self.command_with_data(
-            command::BORDER_WAVEFORM_CONTROL,
-            &[flag::BORDER_WAVEFORM_FOLLOW_LUT | flag::BORDER_WAVEFORM_LUT1],
+            command::DISPLAY_UPDATE_CONTROL,
+            &CTRL::DISPLAY_UPDATE_CONTROL,
         )
         .await?;
-        self.command_with_data(command::DISPLAY_UPDATE_CONTROL, &[0x00, 0x80])
-            .await?;
-        self.command_with_data(command::TEMP_CONTROL, &[flag::INTERNAL_TEMP_SENSOR])
-            .await?;
-        self.use_full_frame().await?;
-        self.wait_until_idle().await;
+        for (command, data) in CTRL::INIT_SEQUENCE {
+            self.command_with_data(*command, data).await?;
+        }
+        if let Some(temperature) = self.temperature {
+            self.write_temperature(temperature).await?;
+        }
         Ok(())
     }
 
     /// Perform a hardware reset of the display.
-    pub async fn hw_reset(&mut self) {
-        self.reset.set_low().unwrap();
+    pub async fn hw_reset(&mut self) -> Result<()> {
+        self.reset.set_low().map_err(|_| Error::PinError)?;
         self.delay.delay_ms(Self::RESET_DELAY_MS).await;
-        self.reset.set_high().unwrap();
+        self.reset.set_high().map_err(|_| Error::PinError)?;
         self.delay.delay_ms(Self::RESET_DELAY_MS).await;
+        Ok(())
     }
 
     /// Write to the B/W buffer.
     pub async fn write_bw_buffer(&mut self, buffer: &[u8]) -> Result<()> {
         self.use_full_frame().await?;
-        self.command_with_data(command::WRITE_BW_DATA, buffer)
-            .await?;
+        self.write_ram(command::WRITE_BW_DATA, buffer).await?;
         Ok(())
     }
 
@@ -141,14 +537,13 @@ where
     /// On B/W displays this buffer is used for fast refreshes.
     pub async fn write_red_buffer(&mut self, buffer: &[u8]) -> Result<()> {
         self.use_full_frame().await?;
-        self.command_with_data(command::WRITE_RED_DATA, buffer)
-            .await?;
+        self.write_ram(command::WRITE_RED_DATA, buffer).await?;
         Ok(())
     }
 
     /// Write to the B/W buffer at the given position.
     ///
-    /// `x`, and `width` must be multiples of 8.
+    /// `x`, and `width` must be multiples of 8, otherwise [`Error::UnalignedRegion`] is returned.
     pub async fn write_partial_bw_buffer(
         &mut self,
         buffer: &[u8],
@@ -158,14 +553,13 @@ where
         height: u32,
     ) -> Result<()> {
         self.use_partial_frame(x, y, width, height).await?;
-        self.command_with_data(command::WRITE_BW_DATA, buffer)
-            .await?;
+        self.write_ram(command::WRITE_BW_DATA, buffer).await?;
         Ok(())
     }
 
     /// Write to the red buffer at the given position.
     ///
-    /// `x`, and `width` must be multiples of 8.
+    /// `x`, and `width` must be multiples of 8, otherwise [`Error::UnalignedRegion`] is returned.
     ///
     /// On B/W displays this buffer is used for fast refreshes.
     pub async fn write_partial_red_buffer(
@@ -177,8 +571,7 @@ where
         height: u32,
     ) -> Result<()> {
         self.use_partial_frame(x, y, width, height).await?;
-        self.command_with_data(command::WRITE_RED_DATA, buffer)
-            .await?;
+        self.write_ram(command::WRITE_RED_DATA, buffer).await?;
         Ok(())
     }
 
@@ -212,11 +605,30 @@ where
     pub async fn full_refresh(&mut self) -> Result<()> {
         self.initial_full_refresh_done = true;
         self.using_partial_mode = false;
+        self.full_refresh_count = self.full_refresh_count.wrapping_add(1);
+        self.fast_refreshes_since_full_refresh = 0;
+        self.ms_since_full_refresh = 0;
+
+        if self.bypassing_red_ram {
+            self.command_with_data(
+                command::DISPLAY_UPDATE_CONTROL,
+                &CTRL::DISPLAY_UPDATE_CONTROL,
+            )
+            .await?;
+            self.bypassing_red_ram = false;
+        }
 
-        self.command_with_data(command::UPDATE_DISPLAY_CTRL2, &[flag::DISPLAY_MODE_1])
+        let update_control = if let Some(lut) = self.full_refresh_lut {
+            self.command_with_data(command::WRITE_LUT, lut.as_bytes())
+                .await?;
+            flag::DISPLAY_MODE_1_REGISTER_LUT
+        } else {
+            self.update_control(CTRL::FULL_REFRESH_UPDATE_CONTROL)
+        };
+        self.command_with_data(command::UPDATE_DISPLAY_CTRL2, &[update_control])
             .await?;
         self.command(command::MASTER_ACTIVATE).await?;
-        self.wait_until_idle().await;
+        self.wait_until_idle().await?;
         Ok(())
     }
 
@@ -237,8 +649,10 @@ where
 
     /// Wake the device up from deep-sleep mode.
     pub async fn wake_up(&mut self) -> Result<()> {
-        // HW reset seems to be enough in deep sleep mode 1, no need to call init again
-        self.hw_reset().await;
+        // HW reset seems to be enough in deep sleep mode 1, no need to call init again. The RAM is
+        // kept but the registers have to be restored.
+        self.hw_reset().await?;
+        self.restore_registers().await?;
         Ok(())
     }
 
@@ -248,10 +662,30 @@ where
     }
 
     async fn use_partial_frame(&mut self, x: u32, y: u32, width: u32, height: u32) -> Result<()> {
-        // TODO: make sure positions are byte-aligned
-        self.set_ram_area(x, y, x + width - 1, y + height - 1)
-            .await?;
-        self.set_ram_counter(x, y).await?;
+        if width == 0
+            || height == 0
+            || x.checked_add(width).is_none_or(|end_x| end_x > WIDTH)
+            || y.checked_add(height).is_none_or(|end_y| end_y > HEIGHT)
+        {
+            return Err(Error::InvalidRegion);
+        }
+        // x is positioned in bytes so the window has to start and end at a byte boundary
+        if !x.is_multiple_of(8) || !width.is_multiple_of(8) {
+            return Err(Error::UnalignedRegion);
+        }
+        // The counters start at the first address of the window in the direction they move in.
+        let (start_x, end_x) = if self.orientation.mirrors_x() {
+            (WIDTH - 1 - x, WIDTH - x - width)
+        } else {
+            (x, x + width - 1)
+        };
+        let (start_y, end_y) = if self.orientation.mirrors_y() {
+            (HEIGHT - 1 - y, HEIGHT - y - height)
+        } else {
+            (y, y + height - 1)
+        };
+        self.set_ram_area(start_x, start_y, end_x, end_y).await?;
+        self.set_ram_counter(start_x, start_y).await?;
         Ok(())
     }
 
@@ -262,36 +696,26 @@ where
         end_x: u32,
         end_y: u32,
     ) -> Result<()> {
-        assert!(start_x < end_x);
-        assert!(start_y < end_y);
-
-        self.command_with_data(
-            command::SET_RAMXPOS,
-            &[(start_x >> 3) as u8, (end_x >> 3) as u8],
-        )
-        .await?;
+        // x is positioned in bytes
+        let (data, len) = encode_range(start_x >> 3, end_x >> 3, CTRL::RAM_X_ADDRESS_BYTES);
+        self.command_with_data(command::SET_RAMXPOS, &data[..len])
+            .await?;
 
-        self.command_with_data(
-            command::SET_RAMYPOS,
-            &[
-                start_y as u8,
-                (start_y >> 8) as u8,
-                end_y as u8,
-                (end_y >> 8) as u8,
-            ],
-        )
-        .await?;
+        let (data, len) = encode_range(start_y, end_y, CTRL::RAM_Y_ADDRESS_BYTES);
+        self.command_with_data(command::SET_RAMYPOS, &data[..len])
+            .await?;
         Ok(())
     }
 
     async fn set_ram_counter(&mut self, x: u32, y: u32) -> Result<()> {
         // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
         // aren't relevant
-        self.command_with_data(command::SET_RAMX_COUNTER, &[(x >> 3) as u8])
+        let (data, len) = encode_address(x >> 3, CTRL::RAM_X_ADDRESS_BYTES);
+        self.command_with_data(command::SET_RAMX_COUNTER, &data[..len])
             .await?;
 
-        // 2 Databytes: A[7:0] & 0..A[8]
-        self.command_with_data(command::SET_RAMY_COUNTER, &[y as u8, (y >> 8) as u8])
+        let (data, len) = encode_address(y, CTRL::RAM_Y_ADDRESS_BYTES);
+        self.command_with_data(command::SET_RAMY_COUNTER, &data[..len])
             .await?;
         Ok(())
     }
@@ -307,19 +731,42 @@ where
     /// Send an array of bytes to the display.
     async fn data(&mut self, data: &[u8]) -> Result<()> {
         self.interface.send_data(DataFormat::U8(data)).await?;
-        self.wait_until_idle().await;
+        self.wait_until_idle().await?;
         Ok(())
     }
 
     /// Waits until device isn't busy anymore (busy == HIGH).
-    async fn wait_until_idle(&mut self) {
+    ///
+    /// Returns [`Error::BusyTimeout`] if the busy timeout is exceeded.
+    async fn wait_until_idle(&mut self) -> Result<()> {
         #[cfg(feature = "blocking")]
-        while self.busy.is_high().unwrap_or(true) {
-            self.delay.delay_ms(1)
+        {
+            let mut waited_ms = 0;
+            while self.busy.is_high().map_err(|_| Error::PinError)? {
+                if self
+                    .busy_timeout_ms
+                    .is_some_and(|timeout| waited_ms >= timeout)
+                {
+                    return Err(Error::BusyTimeout);
+                }
+                self.delay.delay_ms(1);
+                waited_ms += 1;
+            }
+            Ok(())
         }
 
         #[cfg(not(feature = "blocking"))]
-        let _ = self.busy.wait_for_low().await;
+        match self.busy_timeout_ms {
+            Some(timeout) => {
+                let idle = self.busy.wait_for_low();
+                let timeout = self.delay.delay_ms(timeout);
+                match with_timeout(idle, timeout).await {
+                    Some(result) => result.map_err(|_| Error::PinError),
+                    None => Err(Error::BusyTimeout),
+                }
+            }
+            None => self.busy.wait_for_low().await.map_err(|_| Error::PinError),
+        }
     }
 
     /// Sending a command and the data belonging to it.
@@ -329,9 +776,83 @@ where
         Ok(())
     }
 
+    /// Write the given region of a full frame buffer that is `buffer_width` pixels wide to the RAM
+    /// selected by `command`, without copying it to a separate buffer.
+    #[allow(clippy::too_many_arguments)]
+    async fn write_window(
+        &mut self,
+        command: u8,
+        buffer: &[u8],
+        buffer_width: u32,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<()> {
+        let bytes_per_line = buffer_width.div_ceil(8) as usize;
+        let in_buffer = x
+            .checked_add(width)
+            .is_some_and(|end_x| end_x <= buffer_width)
+            && y.checked_add(height)
+                .and_then(|end_y| bytes_per_line.checked_mul(end_y as usize))
+                .is_some_and(|len| len <= buffer.len());
+        if !in_buffer {
+            return Err(Error::InvalidRegion);
+        }
+        self.use_partial_frame(x, y, width, height).await?;
+        let columns = (x / 8) as usize..((x + width) / 8) as usize;
+        let mut data = buffer
+            .chunks(bytes_per_line)
+            .skip(y as usize)
+            .take(height as usize)
+            .flat_map(|line| &line[columns.clone()])
+            .copied();
+        self.write_ram_iter(command, &mut data).await
+    }
+
+    /// Write `data` to the RAM selected by `command`.
+    ///
+    /// The bits of every byte are reversed when the orientation mirrors the image horizontally.
+    async fn write_ram(&mut self, command: u8, data: &[u8]) -> Result<()> {
+        if self.orientation.mirrors_x() {
+            let mut reversed = data.iter().map(|byte| byte.reverse_bits());
+            self.command_with_data_iter(command, &mut reversed).await
+        } else {
+            self.command_with_data(command, data).await
+        }
+    }
+
+    /// Write `data` to the RAM selected by `command` from an iterator.
+    ///
+    /// The bits of every byte are reversed when the orientation mirrors the image horizontally.
+    async fn write_ram_iter(
+        &mut self,
+        command: u8,
+        data: &mut dyn Iterator<Item = u8>,
+    ) -> Result<()> {
+        if self.orientation.mirrors_x() {
+            let mut reversed = data.map(u8::reverse_bits);
+            self.command_with_data_iter(command, &mut reversed).await
+        } else {
+            self.command_with_data_iter(command, data).await
+        }
+    }
+
+    /// Sending a command and the data belonging to it from an iterator.
+    async fn command_with_data_iter(
+        &mut self,
+        command: u8,
+        data: &mut dyn Iterator<Item = u8>,
+    ) -> Result<()> {
+        self.command(command).await?;
+        self.interface.send_data(DataFormat::U8Iter(data)).await?;
+        self.wait_until_idle().await?;
+        Ok(())
+    }
+
     /// Send a byte to the display mutiple times.
     async fn data_x_times(&mut self, data: u8, repetitions: u32) -> Result<()> {
-        let mut iter = iter::repeat(data).take(repetitions as usize);
+        let mut iter = iter::repeat_n(data, repetitions as usize);
         self.interface
             .send_data(DataFormat::U8Iter(&mut iter))
             .await?;
@@ -339,6 +860,125 @@ where
     }
 }
 
+/// Waits for `future` to complete unless `timeout` completes first.
+///
+/// Returns `None` on timeout.
+#[cfg(not(feature = "blocking"))]
+async fn with_timeout<F: Future>(
+    future: F,
+    timeout: impl Future<Output = ()>,
+) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let mut timeout = pin!(timeout);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        if timeout.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+/// A byte-aligned window `(x, y, width, height)` of a frame.
+type Window = (u32, u32, u32, u32);
+
+/// Maximum number of windows [`DisplayDriver::fast_update_diff_from_buffer`] sends before it
+/// sends the full frame instead.
+const MAX_CHANGED_WINDOWS: usize = 8;
+
+/// Rough cost in bytes of sending a window: the RAM area, RAM counter and write commands with
+/// their data, each followed by a busy check.
+const WINDOW_OVERHEAD_BYTES: usize = 32;
+
+/// Returns the byte-aligned windows that together cover all differences between `buffer` and
+/// `previous`.
+///
+/// Lines with changes are combined into a single window when the unchanged lines between them
+/// are cheaper to send again than starting a new window.
+fn changed_windows<'a>(
+    buffer: &'a [u8],
+    previous: &'a [u8],
+    bytes_per_line: usize,
+) -> impl Iterator<Item = Window> + 'a {
+    let height = buffer.len() / bytes_per_line;
+    let changed_columns = move |y: usize| {
+        let line = &buffer[y * bytes_per_line..][..bytes_per_line];
+        let previous_line = &previous[y * bytes_per_line..][..bytes_per_line];
+        let differs = |(a, b): (&u8, &u8)| a != b;
+        let first = line.iter().zip(previous_line).position(differs)?;
+        let last = line.iter().zip(previous_line).rposition(differs)?;
+        Some((y, first, last))
+    };
+
+    let mut next_y = 0;
+    iter::from_fn(move || {
+        let (start_y, mut first, mut last) = (next_y..height).find_map(changed_columns)?;
+        let mut end_y = start_y;
+        // A gap longer than the overhead is never worth sending, so don't look further.
+        let search_end = height.min(end_y + WINDOW_OVERHEAD_BYTES + 2);
+        while let Some((y, line_first, line_last)) =
+            (end_y + 1..search_end).find_map(changed_columns)
+        {
+            let (merged_first, merged_last) = (first.min(line_first), last.max(line_last));
+            let gap = y - end_y - 1;
+            if gap * (merged_last - merged_first + 1) > WINDOW_OVERHEAD_BYTES {
+                break;
+            }
+            (first, last, end_y) = (merged_first, merged_last, y);
+        }
+        next_y = end_y + 1;
+        Some((
+            first as u32 * 8,
+            start_y as u32,
+            (last - first + 1) as u32 * 8,
+            (end_y - start_y + 1) as u32,
+        ))
+    })
+}
+
+/// Collects the [`changed_windows`], or returns `None` when sending them is likely slower than
+/// sending the full frame.
+fn diff_windows(
+    buffer: &[u8],
+    previous: &[u8],
+    bytes_per_line: usize,
+) -> Option<([Window; MAX_CHANGED_WINDOWS], usize)> {
+    let mut windows = [(0, 0, 0, 0); MAX_CHANGED_WINDOWS];
+    let mut count = 0;
+    let mut cost = 0;
+    for window @ (_, _, width, height) in changed_windows(buffer, previous, bytes_per_line) {
+        cost += (width / 8 * height) as usize + WINDOW_OVERHEAD_BYTES;
+        if count == MAX_CHANGED_WINDOWS || cost >= buffer.len() {
+            return None;
+        }
+        windows[count] = window;
+        count += 1;
+    }
+    Some((windows, count))
+}
+
+/// Encodes a RAM address as a `bytes` wide little-endian value.
+///
+/// Returns the encoded data and its length.
+fn encode_address(address: u32, bytes: usize) -> ([u8; 4], usize) {
+    let mut data = [0; 4];
+    data[..bytes].copy_from_slice(&address.to_le_bytes()[..bytes]);
+    (data, bytes)
+}
+
+/// Encodes a RAM address range as `bytes` wide little-endian start and end addresses.
+///
+/// Returns the encoded data and its length.
+fn encode_range(start: u32, end: u32, bytes: usize) -> ([u8; 8], usize) {
+    let mut data = [0; 8];
+    data[..bytes].copy_from_slice(&encode_address(start, bytes).0[..bytes]);
+    data[bytes..2 * bytes].copy_from_slice(&encode_address(end, bytes).0[..bytes]);
+    (data, 2 * bytes)
+}
+
 /// Functions available only for B/W displays
 #[maybe_async_cfg::maybe(
     sync(
@@ -351,18 +991,26 @@ where
     ),
     async(not(feature = "blocking"), keep_self)
 )]
-impl<DI, BSY, RST, DELAY, const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32>
-    DisplayDriver<DI, BSY, RST, DELAY, WIDTH, VISIBLE_WIDTH, HEIGHT, Color>
+impl<DI, BSY, RST, DELAY, const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, CTRL>
+    DisplayDriver<DI, BSY, RST, DELAY, WIDTH, VISIBLE_WIDTH, HEIGHT, Color, CTRL>
 where
     DI: AsyncWriteOnlyDataCommand,
     BSY: InputPin + Wait,
     RST: OutputPin,
     DELAY: DelayNs,
+    CTRL: Controller,
 {
     /// Start a fast refresh of the display using the current in-screen buffers.
     ///
-    /// If the display hasn't done a [`Self::full_refresh`] yet, it will do that first.
+    /// If the display hasn't done a [`Self::full_refresh`] yet, it will do that first. When the
+    /// [`RefreshPolicy`] requires it a full refresh is done instead, which uses the red buffer as
+    /// the previous image. The fast update methods write the new frame to the red buffer first
+    /// in that case, like [`Self::full_update_from_buffer`] does.
     pub async fn fast_refresh(&mut self) -> Result<()> {
+        if self.full_refresh_due() {
+            return self.full_refresh().await;
+        }
+
         if !self.initial_full_refresh_done {
             // There a bug here which causes the new image to overwrite the existing image which then
             // fades out over several updates.
@@ -370,17 +1018,96 @@ where
         }
 
         if !self.using_partial_mode {
-            self.command_with_data(command::WRITE_LUT, &lut::LUT_PARTIAL_UPDATE)
-                .await?;
+            let custom_lut = self.fast_refresh_lut;
+            if let Some(lut) = custom_lut
+                .as_ref()
+                .map(Lut::as_bytes)
+                .or(CTRL::FAST_REFRESH_LUT)
+            {
+                self.command_with_data(command::WRITE_LUT, lut).await?;
+            }
+            for (command, data) in CTRL::FAST_REFRESH_VOLTAGES {
+                self.command_with_data(*command, data).await?;
+            }
             self.using_partial_mode = true;
         }
-        self.command_with_data(command::UPDATE_DISPLAY_CTRL2, &[flag::UNDOCUMENTED])
+        // The controller's update control may load the OTP waveform, which would replace a custom one.
+        let update_control = self.update_control(if self.fast_refresh_lut.is_some() {
+            CTRL::FAST_REFRESH_REGISTER_LUT_UPDATE_CONTROL
+        } else {
+            CTRL::FAST_REFRESH_UPDATE_CONTROL
+        });
+        self.command_with_data(command::UPDATE_DISPLAY_CTRL2, &[update_control])
             .await?;
         self.command(command::MASTER_ACTIVATE).await?;
-        self.wait_until_idle().await;
+        self.wait_until_idle().await?;
+        self.fast_refresh_count = self.fast_refresh_count.wrapping_add(1);
+        self.fast_refreshes_since_full_refresh =
+            self.fast_refreshes_since_full_refresh.saturating_add(1);
+        Ok(())
+    }
+
+    /// Start a grayscale refresh of the display using the current in-screen buffers.
+    ///
+    /// Loads the controller's 4-level grayscale waveform, which shows the B/W RAM as the high bit
+    /// and the red RAM as the low bit of the gray level of every pixel. The fast refresh waveform
+    /// is reloaded afterwards and the next fast refresh does a full refresh first, so the
+    /// controller is back in its normal state.
+    ///
+    /// Returns [`Error::Unsupported`] if the controller has no grayscale waveform, see
+    /// [`Controller::GRAY_REFRESH_SETUP`].
+    pub async fn gray_refresh(&mut self) -> Result<()> {
+        let Some(setup) = CTRL::GRAY_REFRESH_SETUP else {
+            return Err(Error::Unsupported);
+        };
+        for (command, data) in setup {
+            self.command_with_data(*command, data).await?;
+        }
+        self.using_partial_mode = false;
+        self.initial_full_refresh_done = false;
+
+        self.command_with_data(
+            command::UPDATE_DISPLAY_CTRL2,
+            &[flag::DISPLAY_MODE_1_REGISTER_LUT],
+        )
+        .await?;
+        self.command(command::MASTER_ACTIVATE).await?;
+        self.wait_until_idle().await?;
+        Ok(())
+    }
+
+    /// Update the screen with the provided full frame grayscale buffers using a grayscale refresh.
+    ///
+    /// `bw_buffer` holds the high bit and `red_buffer` the low bit of every gray level. Returns
+    /// [`Error::Unsupported`] without writing anything if the controller doesn't support
+    /// grayscale.
+    pub async fn gray_update_from_buffer(
+        &mut self,
+        bw_buffer: &[u8],
+        red_buffer: &[u8],
+    ) -> Result<()> {
+        if CTRL::GRAY_REFRESH_SETUP.is_none() {
+            return Err(Error::Unsupported);
+        }
+        self.write_red_buffer(red_buffer).await?;
+        self.write_bw_buffer(bw_buffer).await?;
+        self.gray_refresh().await?;
         Ok(())
     }
 
+    /// Update the screen with the provided grayscale [`Display`] using a grayscale refresh.
+    ///
+    /// See [`Self::gray_refresh`] for details.
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+    #[cfg(feature = "graphics")]
+    pub async fn gray_update<const BUFFER_SIZE: usize>(
+        &mut self,
+        display: &Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, GrayLevel>,
+    ) -> Result<()> {
+        self.gray_update_from_buffer(display.bw_buffer(), display.red_buffer())
+            .await
+    }
+
     /// Update the screen with the provided full frame buffer using a full refresh.
     pub async fn full_update_from_buffer(&mut self, buffer: &[u8]) -> Result<()> {
         self.write_red_buffer(buffer).await?;
@@ -393,6 +1120,9 @@ where
 
     /// Update the screen with the provided full frame buffer using a fast refresh.
     pub async fn fast_update_from_buffer(&mut self, buffer: &[u8]) -> Result<()> {
+        if self.full_refresh_due() {
+            self.write_red_buffer(buffer).await?;
+        }
         self.write_bw_buffer(buffer).await?;
         self.fast_refresh().await?;
         self.write_red_buffer(buffer).await?;
@@ -400,9 +1130,65 @@ where
         Ok(())
     }
 
+    /// Update the screen with the provided full frame buffer using a fast refresh, only sending the
+    /// parts that differ from `previous`.
+    ///
+    /// `previous` must contain the frame that is currently shown on the display. It is updated to
+    /// `buffer` afterwards so it can be passed again for the next update. Keep in mind that the
+    /// first update after initializing the display should send the full frame, for example with
+    /// [`Self::full_update_from_buffer`].
+    ///
+    /// When the changes are spread over so many windows that sending them would be slower, the
+    /// full frame is sent like [`Self::fast_update_from_buffer`] does.
+    ///
+    /// Does nothing if the buffers are equal. Returns [`Error::InvalidBufferSize`] if either buffer
+    /// doesn't have the size of a full frame.
+    pub async fn fast_update_diff_from_buffer(
+        &mut self,
+        buffer: &[u8],
+        previous: &mut [u8],
+    ) -> Result<()> {
+        let bytes_per_line = WIDTH.div_ceil(8) as usize;
+        let frame_size = bytes_per_line * HEIGHT as usize;
+        if buffer.len() != frame_size || previous.len() != frame_size {
+            return Err(Error::InvalidBufferSize);
+        }
+        if buffer == previous {
+            return Ok(());
+        }
+
+        let (windows, count) = match diff_windows(buffer, previous, bytes_per_line) {
+            Some(windows) => windows,
+            None => {
+                self.fast_update_from_buffer(buffer).await?;
+                previous.copy_from_slice(buffer);
+                return Ok(());
+            }
+        };
+        let windows = &windows[..count];
+        let full_refresh_due = self.full_refresh_due();
+        for &(x, y, width, height) in windows {
+            if full_refresh_due {
+                self.write_window(command::WRITE_RED_DATA, buffer, WIDTH, x, y, width, height)
+                    .await?;
+            }
+            self.write_window(command::WRITE_BW_DATA, buffer, WIDTH, x, y, width, height)
+                .await?;
+        }
+        self.fast_refresh().await?;
+        for &(x, y, width, height) in windows {
+            self.write_window(command::WRITE_RED_DATA, buffer, WIDTH, x, y, width, height)
+                .await?;
+            self.write_window(command::WRITE_BW_DATA, buffer, WIDTH, x, y, width, height)
+                .await?;
+        }
+        previous.copy_from_slice(buffer);
+        Ok(())
+    }
+
     /// Update the screen with the provided partial frame buffer at the given position using a fast refresh.
     ///
-    /// `x`, and `width` must be multiples of 8.
+    /// `x`, and `width` must be multiples of 8, otherwise [`Error::UnalignedRegion`] is returned.
     pub async fn fast_partial_update_from_buffer(
         &mut self,
         buffer: &[u8],
@@ -411,6 +1197,10 @@ where
         width: u32,
         height: u32,
     ) -> Result<()> {
+        if self.full_refresh_due() {
+            self.write_partial_red_buffer(buffer, x, y, width, height)
+                .await?;
+        }
         self.write_partial_bw_buffer(buffer, x, y, width, height)
             .await?;
         self.fast_refresh().await?;
@@ -426,7 +1216,7 @@ where
     #[cfg(feature = "graphics")]
     pub async fn full_update<const BUFFER_SIZE: usize>(
         &mut self,
-        display: &Display<WIDTH, HEIGHT, BUFFER_SIZE, Color>,
+        display: &Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, Color>,
     ) -> Result<()> {
         self.full_update_from_buffer(display.buffer()).await
     }
@@ -436,29 +1226,91 @@ where
     #[cfg(feature = "graphics")]
     pub async fn fast_update<const BUFFER_SIZE: usize>(
         &mut self,
-        display: &Display<WIDTH, HEIGHT, BUFFER_SIZE, Color>,
+        display: &Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, Color>,
     ) -> Result<()> {
         self.fast_update_from_buffer(display.buffer()).await
     }
 
-    /// Update the screen with the provided partial [`Display`] at the given position using a fast refresh.
+    /// Update the changed part of the screen with the provided [`Display`] using a fast refresh.
     ///
-    /// `x` and the display width `W` must be multiples of 8.
+    /// Only the [`Display::dirty_area`] is sent to the display, after which the dirty state of
+    /// `display` is cleared. Does nothing if nothing changed.
     #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
     #[cfg(feature = "graphics")]
-    pub async fn fast_partial_update<const W: u32, const H: u32, const BUFFER_SIZE: usize>(
+    pub async fn fast_update_dirty<const BUFFER_SIZE: usize>(
         &mut self,
-        display: &Display<W, H, BUFFER_SIZE, Color>,
-        x: u32,
-        y: u32,
+        display: &mut Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, Color>,
     ) -> Result<()> {
-        self.fast_partial_update_from_buffer(display.buffer(), x, y, W, H)
-            .await
+        let Some(area) = display.dirty_area() else {
+            return Ok(());
+        };
+        self.fast_update_area(display.buffer(), area).await?;
+        display.clear_dirty();
+        Ok(())
     }
-}
 
-/// Functions available only for tri-color displays
-#[maybe_async_cfg::maybe(
+    /// Update the part of the screen covered by the provided [`Viewport`] using a fast refresh.
+    ///
+    /// The rows of the [`Viewport::buffer_area`] are sent straight from the parent display's
+    /// buffer. Does nothing if the viewport is empty.
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+    #[cfg(feature = "graphics")]
+    pub async fn fast_update_viewport<const BUFFER_SIZE: usize>(
+        &mut self,
+        viewport: &Viewport<'_, WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, Color>,
+    ) -> Result<()> {
+        let Some(area) = viewport.buffer_area() else {
+            return Ok(());
+        };
+        self.fast_update_area(viewport.display().buffer(), area)
+            .await
+    }
+
+    /// Update the given byte-aligned area of the screen from a full frame buffer using a fast
+    /// refresh.
+    #[cfg(feature = "graphics")]
+    async fn fast_update_area(&mut self, buffer: &[u8], area: Rectangle) -> Result<()> {
+        let (x, y) = (area.top_left.x as u32, area.top_left.y as u32);
+        let (width, height) = (area.size.width, area.size.height);
+
+        if self.full_refresh_due() {
+            self.write_window(command::WRITE_RED_DATA, buffer, WIDTH, x, y, width, height)
+                .await?;
+        }
+        self.write_window(command::WRITE_BW_DATA, buffer, WIDTH, x, y, width, height)
+            .await?;
+        self.fast_refresh().await?;
+        self.write_window(command::WRITE_RED_DATA, buffer, WIDTH, x, y, width, height)
+            .await?;
+        self.write_window(command::WRITE_BW_DATA, buffer, WIDTH, x, y, width, height)
+            .await?;
+        Ok(())
+    }
+
+    /// Update the screen with the provided partial [`Display`] at the given position using a fast refresh.
+    ///
+    /// `x` and the display width `W` must be multiples of 8, otherwise [`Error::UnalignedRegion`] is
+    /// returned.
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+    #[cfg(feature = "graphics")]
+    pub async fn fast_partial_update<
+        const W: u32,
+        const VW: u32,
+        const H: u32,
+        const BUFFER_SIZE: usize,
+    >(
+        &mut self,
+        display: &Display<W, VW, H, BUFFER_SIZE, Color>,
+        x: u32,
+        y: u32,
+    ) -> Result<()> {
+        self.fast_partial_update_from_buffer(display.buffer(), x, y, W, H)
+            .await
+    }
+}
+
+/// Functions available only for tri-color displays
+#[maybe_async_cfg::maybe(
     sync(
         feature = "blocking",
         keep_self,
@@ -469,13 +1321,14 @@ where
     ),
     async(not(feature = "blocking"), keep_self)
 )]
-impl<DI, BSY, RST, DELAY, const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32>
-    DisplayDriver<DI, BSY, RST, DELAY, WIDTH, VISIBLE_WIDTH, HEIGHT, TriColor>
+impl<DI, BSY, RST, DELAY, const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, CTRL>
+    DisplayDriver<DI, BSY, RST, DELAY, WIDTH, VISIBLE_WIDTH, HEIGHT, TriColor, CTRL>
 where
     DI: AsyncWriteOnlyDataCommand,
     BSY: InputPin + Wait,
     RST: OutputPin,
     DELAY: DelayNs,
+    CTRL: Controller,
 {
     /// Update the screen with the provided full frame buffers using a full refresh.
     pub async fn full_update_from_buffer(
@@ -489,16 +1342,1115 @@ where
         Ok(())
     }
 
+    /// Update the screen with the provided partial frame buffers at the given position using a full refresh.
+    ///
+    /// Only the given region is sent to the display but the whole screen is refreshed.
+    ///
+    /// `x`, and `width` must be multiples of 8, otherwise [`Error::UnalignedRegion`] is returned.
+    pub async fn full_partial_update_from_buffer(
+        &mut self,
+        bw_buffer: &[u8],
+        red_buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<()> {
+        self.write_partial_red_buffer(red_buffer, x, y, width, height)
+            .await?;
+        self.write_partial_bw_buffer(bw_buffer, x, y, width, height)
+            .await?;
+        self.full_refresh().await?;
+        Ok(())
+    }
+
     /// Update the screen with the provided [`Display`] using a full refresh.
     #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
     #[cfg(feature = "graphics")]
     pub async fn full_update<const BUFFER_SIZE: usize>(
         &mut self,
-        display: &Display<WIDTH, HEIGHT, BUFFER_SIZE, TriColor>,
+        display: &Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, TriColor>,
     ) -> Result<()> {
         self.full_update_from_buffer(display.bw_buffer(), display.red_buffer())
             .await
     }
 
-    // TODO: check if partial updates with full refresh are supported
+    /// Update the screen with the provided partial [`Display`] at the given position using a full refresh.
+    ///
+    /// Only the region covered by `display` is sent to the display but the whole screen is refreshed.
+    ///
+    /// `x` and the display width `W` must be multiples of 8, otherwise [`Error::UnalignedRegion`] is
+    /// returned.
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+    #[cfg(feature = "graphics")]
+    pub async fn full_partial_update<
+        const W: u32,
+        const VW: u32,
+        const H: u32,
+        const BUFFER_SIZE: usize,
+    >(
+        &mut self,
+        display: &Display<W, VW, H, BUFFER_SIZE, TriColor>,
+        x: u32,
+        y: u32,
+    ) -> Result<()> {
+        self.full_partial_update_from_buffer(display.bw_buffer(), display.red_buffer(), x, y, W, H)
+            .await
+    }
+
+    /// Update the part of the screen covered by the provided [`Viewport`] using a full refresh.
+    ///
+    /// The rows of the [`Viewport::buffer_area`] are sent straight from the parent display's
+    /// buffers but the whole screen is refreshed. Does nothing if the viewport is empty.
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+    #[cfg(feature = "graphics")]
+    pub async fn full_update_viewport<const BUFFER_SIZE: usize>(
+        &mut self,
+        viewport: &Viewport<'_, WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, TriColor>,
+    ) -> Result<()> {
+        let Some(area) = viewport.buffer_area() else {
+            return Ok(());
+        };
+        let (x, y) = (area.top_left.x as u32, area.top_left.y as u32);
+        let (width, height) = (area.size.width, area.size.height);
+        let display = viewport.display();
+
+        self.write_window(
+            command::WRITE_RED_DATA,
+            display.red_buffer(),
+            WIDTH,
+            x,
+            y,
+            width,
+            height,
+        )
+        .await?;
+        self.write_window(
+            command::WRITE_BW_DATA,
+            display.bw_buffer(),
+            WIDTH,
+            x,
+            y,
+            width,
+            height,
+        )
+        .await?;
+        self.full_refresh().await?;
+        Ok(())
+    }
+
+    /// Start a fast refresh of the display that only updates the black and white pixels.
+    ///
+    /// Tri-color displays don't have a fast refresh waveform so this loads a custom B/W-only waveform
+    /// and makes the controller ignore the red buffer. This has some trade-offs compared to a full
+    /// refresh:
+    ///
+    /// * Red pixels are shown in the color of their B/W buffer bit until the next full refresh.
+    /// * Every pixel is driven to black or white without the usual cleaning flashes, so ghosting
+    ///   of the previous image and a faint red tint on previously red pixels build up over several
+    ///   fast refreshes.
+    ///
+    /// Do a [`Self::full_refresh`] regularly to restore the image quality and show red pixels again,
+    /// or set a [`RefreshPolicy`] to do this automatically.
+    pub async fn fast_refresh(&mut self) -> Result<()> {
+        if self.full_refresh_due() {
+            return self.full_refresh().await;
+        }
+
+        if !self.bypassing_red_ram {
+            let [_, source_output_mode] = CTRL::DISPLAY_UPDATE_CONTROL;
+            self.command_with_data(
+                command::DISPLAY_UPDATE_CONTROL,
+                &[flag::BYPASS_RED_RAM, source_output_mode],
+            )
+            .await?;
+            self.bypassing_red_ram = true;
+        }
+        if !self.using_partial_mode {
+            let lut = self.fast_refresh_lut.unwrap_or(Lut::TRI_COLOR_FAST_REFRESH);
+            self.command_with_data(command::WRITE_LUT, lut.as_bytes())
+                .await?;
+            self.using_partial_mode = true;
+        }
+        self.command_with_data(
+            command::UPDATE_DISPLAY_CTRL2,
+            &[flag::DISPLAY_MODE_1_REGISTER_LUT],
+        )
+        .await?;
+        self.command(command::MASTER_ACTIVATE).await?;
+        self.wait_until_idle().await?;
+        self.fast_refresh_count = self.fast_refresh_count.wrapping_add(1);
+        self.fast_refreshes_since_full_refresh =
+            self.fast_refreshes_since_full_refresh.saturating_add(1);
+        Ok(())
+    }
+
+    /// Update the black and white pixels on the screen with the provided full frame B/W buffer using
+    /// a fast refresh.
+    ///
+    /// The red buffer on the display is left alone. See [`Self::fast_refresh`] for the trade-offs
+    /// of fast refreshes on tri-color displays.
+    pub async fn fast_update_from_buffer(&mut self, bw_buffer: &[u8]) -> Result<()> {
+        self.write_bw_buffer(bw_buffer).await?;
+        self.fast_refresh().await?;
+        Ok(())
+    }
+
+    /// Update the black and white pixels on the screen with the provided partial B/W buffer at the
+    /// given position using a fast refresh.
+    ///
+    /// The red buffer on the display is left alone. See [`Self::fast_refresh`] for the trade-offs
+    /// of fast refreshes on tri-color displays.
+    ///
+    /// `x`, and `width` must be multiples of 8, otherwise [`Error::UnalignedRegion`] is returned.
+    pub async fn fast_partial_update_from_buffer(
+        &mut self,
+        bw_buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<()> {
+        self.write_partial_bw_buffer(bw_buffer, x, y, width, height)
+            .await?;
+        self.fast_refresh().await?;
+        Ok(())
+    }
+
+    /// Update the screen with the provided [`Display`] using a fast refresh.
+    ///
+    /// Red pixels are shown as white until the next full refresh. Both buffers are written to the
+    /// display so a later [`Self::full_refresh`] shows the red pixels. See [`Self::fast_refresh`]
+    /// for the other trade-offs of fast refreshes on tri-color displays.
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+    #[cfg(feature = "graphics")]
+    pub async fn fast_update<const BUFFER_SIZE: usize>(
+        &mut self,
+        display: &Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, TriColor>,
+    ) -> Result<()> {
+        self.write_red_buffer(display.red_buffer()).await?;
+        self.use_full_frame().await?;
+        let mut bw_without_red = display
+            .bw_buffer()
+            .iter()
+            .zip(display.red_buffer())
+            .map(|(bw, red)| bw | red);
+        self.write_ram_iter(command::WRITE_BW_DATA, &mut bw_without_red)
+            .await?;
+        self.fast_refresh().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lut;
+    use crate::mock::{Emulator, MockBusyPin, MockDelay, MockInterface, MockResetPin, Transaction};
+    use alloc::vec::Vec;
+
+    /// Runs a driver method in both the async and the blocking configuration.
+    #[cfg(not(feature = "blocking"))]
+    macro_rules! run {
+        ($e:expr) => {
+            crate::mock::block_on($e)
+        };
+    }
+    #[cfg(feature = "blocking")]
+    macro_rules! run {
+        ($e:expr) => {
+            $e
+        };
+    }
+
+    type TestDriver =
+        WeActStudio290BlackWhiteDriver<MockInterface, MockBusyPin, MockResetPin, MockDelay>;
+
+    fn driver() -> (TestDriver, MockInterface, MockBusyPin, MockResetPin) {
+        let interface = MockInterface::new();
+        let busy = MockBusyPin::new();
+        let reset = MockResetPin::new();
+        let driver = TestDriver::new(
+            interface.clone(),
+            busy.clone(),
+            reset.clone(),
+            MockDelay::new(),
+        );
+        (driver, interface, busy, reset)
+    }
+
+    /// Commands with their data. Commands without data only record the command.
+    fn transactions(commands: &[(u8, &[u8])]) -> Vec<Transaction> {
+        let mut transactions = Vec::new();
+        for (command, data) in commands {
+            transactions.push(Transaction::Command(*command));
+            if !data.is_empty() {
+                transactions.push(Transaction::Data(data.to_vec()));
+            }
+        }
+        transactions
+    }
+
+    #[test]
+    fn init() {
+        let (mut driver, interface, _, reset) = driver();
+
+        run!(driver.init()).unwrap();
+
+        assert_eq!(reset.levels(), [false, true]);
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::SW_RESET, &[]),
+                (command::DRIVER_CONTROL, &[0x27, 0x01, 0x00]),
+                (command::DATA_ENTRY_MODE, &[0x03]),
+                (command::DISPLAY_UPDATE_CONTROL, &[0x00, 0x80]),
+                (command::BORDER_WAVEFORM_CONTROL, &[0x05]),
+                (command::TEMP_CONTROL, &[0x80]),
+                (command::SET_RAMXPOS, &[0x00, 0x0F]),
+                (command::SET_RAMYPOS, &[0x00, 0x00, 0x27, 0x01]),
+                (command::SET_RAMX_COUNTER, &[0x00]),
+                (command::SET_RAMY_COUNTER, &[0x00, 0x00]),
+            ])
+        );
+    }
+
+    fn driver_154() -> (
+        WeActStudio154BlackWhiteDriver<MockInterface, MockBusyPin, MockResetPin, MockDelay>,
+        MockInterface,
+    ) {
+        let interface = MockInterface::new();
+        let driver = WeActStudio154BlackWhiteDriver::new(
+            interface.clone(),
+            MockBusyPin::new(),
+            MockResetPin::new(),
+            MockDelay::new(),
+        );
+        (driver, interface)
+    }
+
+    fn driver_420() -> (
+        WeActStudio420BlackWhiteDriver<MockInterface, MockBusyPin, MockResetPin, MockDelay>,
+        MockInterface,
+    ) {
+        let interface = MockInterface::new();
+        let driver = WeActStudio420BlackWhiteDriver::new(
+            interface.clone(),
+            MockBusyPin::new(),
+            MockResetPin::new(),
+            MockDelay::new(),
+        );
+        (driver, interface)
+    }
+
+    #[test]
+    fn init_ssd1681() {
+        let (mut driver, interface) = driver_154();
+
+        run!(driver.init()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::SW_RESET, &[]),
+                (command::DRIVER_CONTROL, &[0xC7, 0x00, 0x00]),
+                (command::DATA_ENTRY_MODE, &[0x03]),
+                (command::DISPLAY_UPDATE_CONTROL, &[0x00, 0x00]),
+                (command::BORDER_WAVEFORM_CONTROL, &[0x05]),
+                (command::TEMP_CONTROL, &[0x80]),
+                (command::SET_RAMXPOS, &[0x00, 0x18]),
+                (command::SET_RAMYPOS, &[0x00, 0x00, 0xC7, 0x00]),
+                (command::SET_RAMX_COUNTER, &[0x00]),
+                (command::SET_RAMY_COUNTER, &[0x00, 0x00]),
+            ])
+        );
+    }
+
+    #[test]
+    fn fast_refresh_ssd1681() {
+        let (mut driver, interface) = driver_154();
+
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::UPDATE_DISPLAY_CTRL2, &[0xF7]),
+                (command::MASTER_ACTIVATE, &[]),
+                (command::WRITE_LUT, &lut::LUT_PARTIAL_UPDATE_SSD1681),
+                (command::END_OPTION, &[0x02]),
+                (command::GATE_VOLTAGE, &[0x17]),
+                (command::SOURCE_VOLTAGE, &[0x41, 0xB0, 0x32]),
+                (command::WRITE_VCOM, &[0x28]),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xCF]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn init_ssd1683() {
+        let (mut driver, interface) = driver_420();
+
+        run!(driver.init()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::SW_RESET, &[]),
+                (command::DRIVER_CONTROL, &[0x2B, 0x01, 0x00]),
+                (command::DATA_ENTRY_MODE, &[0x03]),
+                (command::DISPLAY_UPDATE_CONTROL, &[0x00, 0x00]),
+                (command::BORDER_WAVEFORM_CONTROL, &[0x05]),
+                (command::TEMP_CONTROL, &[0x80]),
+                (command::SET_RAMXPOS, &[0x00, 0x31]),
+                (command::SET_RAMYPOS, &[0x00, 0x00, 0x2B, 0x01]),
+                (command::SET_RAMX_COUNTER, &[0x00]),
+                (command::SET_RAMY_COUNTER, &[0x00, 0x00]),
+            ])
+        );
+    }
+
+    #[test]
+    fn partial_write_at_far_corner_ssd1683() {
+        let (mut driver, interface) = driver_420();
+
+        run!(driver.write_partial_bw_buffer(&[0x00], 392, 299, 8, 1)).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::SET_RAMXPOS, &[0x31, 0x31]),
+                (command::SET_RAMYPOS, &[0x2B, 0x01, 0x2B, 0x01]),
+                (command::SET_RAMX_COUNTER, &[0x31]),
+                (command::SET_RAMY_COUNTER, &[0x2B, 0x01]),
+                (command::WRITE_BW_DATA, &[0x00]),
+            ])
+        );
+    }
+
+    #[test]
+    fn fast_refresh_ssd1683() {
+        let (mut driver, interface) = driver_420();
+
+        run!(driver.fast_refresh()).unwrap();
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::UPDATE_DISPLAY_CTRL2, &[0xF7]),
+                (command::MASTER_ACTIVATE, &[]),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xFF]),
+                (command::MASTER_ACTIVATE, &[]),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xFF]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn reversed_gate_scan() {
+        let (mut driver, interface, _, _) = driver();
+
+        run!(driver.set_gate_scan_reversed(true)).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[(command::DRIVER_CONTROL, &[0x27, 0x01, 0x01])])
+        );
+    }
+
+    #[test]
+    fn wake_up_restores_gate_scan_direction() {
+        let (mut driver, interface, _, reset) = driver();
+        run!(driver.set_gate_scan_reversed(true)).unwrap();
+        run!(driver.sleep()).unwrap();
+        interface.take_transactions();
+
+        run!(driver.wake_up()).unwrap();
+
+        assert_eq!(reset.levels(), [false, true]);
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::DRIVER_CONTROL, &[0x27, 0x01, 0x01]),
+                (command::DATA_ENTRY_MODE, &[0x03]),
+                (command::DISPLAY_UPDATE_CONTROL, &[0x00, 0x80]),
+                (command::BORDER_WAVEFORM_CONTROL, &[0x05]),
+                (command::TEMP_CONTROL, &[0x80]),
+            ])
+        );
+    }
+
+    #[test]
+    fn full_refresh() {
+        let (mut driver, interface, _, _) = driver();
+
+        run!(driver.full_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::UPDATE_DISPLAY_CTRL2, &[0xF7]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn fast_refresh_loads_lut_once() {
+        let (mut driver, interface, _, _) = driver();
+
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::UPDATE_DISPLAY_CTRL2, &[0xF7]),
+                (command::MASTER_ACTIVATE, &[]),
+                (command::WRITE_LUT, &lut::LUT_PARTIAL_UPDATE),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xCC]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::UPDATE_DISPLAY_CTRL2, &[0xCC]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn custom_fast_refresh_lut() {
+        let (mut driver, interface, _, _) = driver();
+        run!(driver.full_refresh()).unwrap();
+        interface.take_transactions();
+        let mut custom = Lut::SSD1680_FAST_REFRESH;
+        custom.set_frame_rate(0, 0x3);
+
+        driver.set_fast_refresh_lut(custom).unwrap();
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::WRITE_LUT, custom.as_bytes()),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xCC]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+
+        driver.use_default_luts();
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::WRITE_LUT, &lut::LUT_PARTIAL_UPDATE),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xCC]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn custom_fast_refresh_lut_keeps_controller_voltages() {
+        let interface = MockInterface::new();
+        let mut driver = WeActStudio154BlackWhiteDriver::new(
+            interface.clone(),
+            MockBusyPin::new(),
+            MockResetPin::new(),
+            MockDelay::new(),
+        );
+        run!(driver.full_refresh()).unwrap();
+        interface.take_transactions();
+
+        driver
+            .set_fast_refresh_lut(Lut::SSD1680_FAST_REFRESH)
+            .unwrap();
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::WRITE_LUT, &lut::LUT_PARTIAL_UPDATE),
+                (command::END_OPTION, &[0x02]),
+                (command::GATE_VOLTAGE, &[0x17]),
+                (command::SOURCE_VOLTAGE, &[0x41, 0xB0, 0x32]),
+                (command::WRITE_VCOM, &[0x28]),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xCF]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn custom_full_refresh_lut() {
+        let (mut driver, interface, _, _) = driver();
+
+        driver.set_full_refresh_lut(Lut::SSD1680_GRAY2).unwrap();
+        run!(driver.full_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::WRITE_LUT, &lut::LUT_GRAY2),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xC7]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+
+        driver.use_default_luts();
+        run!(driver.full_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::UPDATE_DISPLAY_CTRL2, &[0xF7]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn external_temperature() {
+        let (mut driver, interface, _, _) = driver();
+
+        run!(driver.set_temperature(-5)).unwrap();
+        run!(driver.full_refresh()).unwrap();
+
+        assert_eq!(driver.temperature(), Some(-5));
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::WRITE_TEMPERATURE, &[0xFB, 0x00]),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xD7]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+
+        run!(driver.use_internal_temperature_sensor()).unwrap();
+        run!(driver.full_refresh()).unwrap();
+
+        assert_eq!(driver.temperature(), None);
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::TEMP_CONTROL, &[0x80]),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xF7]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn fast_refresh_lut_follows_temperature() {
+        // A waveform that differs from the built-in ones in its first byte.
+        let mut cold = *Lut::SSD1680_FAST_REFRESH.as_bytes();
+        cold[0] = 0x5A;
+        let cold = Lut::from_bytes(cold).unwrap();
+        let bands = alloc::boxed::Box::leak(alloc::boxed::Box::new([
+            TemperatureBand {
+                max_temperature: 10,
+                lut: cold,
+            },
+            TemperatureBand {
+                max_temperature: 40,
+                lut: Lut::SSD1680_FAST_REFRESH,
+            },
+        ]));
+        let (mut driver, interface, _, _) = driver();
+        run!(driver.full_refresh()).unwrap();
+        driver.set_fast_refresh_lut_bands(bands).unwrap();
+        run!(driver.set_temperature(5)).unwrap();
+        interface.take_transactions();
+
+        run!(driver.fast_refresh()).unwrap();
+        run!(driver.set_temperature(8)).unwrap();
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::WRITE_LUT, cold.as_bytes()),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xCC]),
+                (command::MASTER_ACTIVATE, &[]),
+                (command::WRITE_TEMPERATURE, &[0x08, 0x00]),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xCC]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+
+        run!(driver.set_temperature(20)).unwrap();
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::WRITE_TEMPERATURE, &[0x14, 0x00]),
+                (command::WRITE_LUT, &lut::LUT_PARTIAL_UPDATE),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xCC]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn invalid_lut_is_rejected() {
+        let (mut driver, _, _, _) = driver();
+
+        assert!(matches!(
+            driver.set_fast_refresh_lut(Lut::new()),
+            Err(Error::InvalidLut)
+        ));
+        assert!(driver.fast_refresh_lut().is_none());
+    }
+
+    #[test]
+    fn gray_refresh_loads_gray_lut_and_restores_fast_refresh() {
+        let (mut driver, interface, _, _) = driver();
+        run!(driver.full_refresh()).unwrap();
+        run!(driver.fast_refresh()).unwrap();
+        interface.take_transactions();
+
+        run!(driver.gray_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::WRITE_LUT, &lut::LUT_GRAY2),
+                (command::END_OPTION, &[0x22]),
+                (command::GATE_VOLTAGE, &[0x17]),
+                (command::SOURCE_VOLTAGE, &[0x41, 0xAE, 0x32]),
+                (command::WRITE_VCOM, &[0x28]),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xC7]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::UPDATE_DISPLAY_CTRL2, &[0xF7]),
+                (command::MASTER_ACTIVATE, &[]),
+                (command::WRITE_LUT, &lut::LUT_PARTIAL_UPDATE),
+                (command::UPDATE_DISPLAY_CTRL2, &[0xCC]),
+                (command::MASTER_ACTIVATE, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn gray_update_is_unsupported_without_gray_waveform() {
+        let (mut driver, interface) = driver_420();
+
+        let result = run!(driver.gray_update_from_buffer(&[0xFF; 50 * 300], &[0xFF; 50 * 300]));
+
+        assert!(matches!(result, Err(Error::Unsupported)));
+        assert_eq!(interface.take_transactions(), []);
+    }
+
+    #[test]
+    fn sleep() {
+        let (mut driver, interface, _, _) = driver();
+
+        run!(driver.sleep()).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[(command::DEEP_SLEEP, &[0x01])])
+        );
+    }
+
+    #[test]
+    fn write_partial_bw_buffer() {
+        let (mut driver, interface, _, _) = driver();
+
+        run!(driver.write_partial_bw_buffer(&[0xAA; 4], 8, 2, 16, 2)).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            transactions(&[
+                (command::SET_RAMXPOS, &[0x01, 0x02]),
+                (command::SET_RAMYPOS, &[0x02, 0x00, 0x03, 0x00]),
+                (command::SET_RAMX_COUNTER, &[0x01]),
+                (command::SET_RAMY_COUNTER, &[0x02, 0x00]),
+                (command::WRITE_BW_DATA, &[0xAA; 4]),
+            ])
+        );
+    }
+
+    #[test]
+    fn unaligned_partial_write_sends_nothing() {
+        let (mut driver, interface, _, _) = driver();
+
+        let result = run!(driver.write_partial_bw_buffer(&[0xAA; 4], 4, 2, 16, 2));
+
+        assert!(matches!(result, Err(Error::UnalignedRegion)));
+        assert_eq!(interface.take_transactions(), []);
+    }
+
+    #[test]
+    fn window_outside_of_buffer_sends_nothing() {
+        let (mut driver, interface, _, _) = driver();
+        let buffer = [0xFF; 2 * 3];
+
+        for (x, y, width, height) in [(0, 0, 16, 4), (8, 0, 16, 3), (0, u32::MAX, 8, 2)] {
+            let result =
+                run!(driver.write_window(command::WRITE_BW_DATA, &buffer, 16, x, y, width, height));
+            assert!(matches!(result, Err(Error::InvalidRegion)));
+        }
+        assert_eq!(interface.take_transactions(), []);
+    }
+
+    #[test]
+    fn waits_for_busy_pin() {
+        let (mut driver, _, busy, _) = driver();
+        busy.busy_for(3);
+        driver.set_busy_timeout(Some(10));
+
+        run!(driver.full_refresh()).unwrap();
+    }
+
+    #[test]
+    fn busy_for_longer_than_timeout() {
+        let (mut driver, _, busy, _) = driver();
+        busy.busy_for(20);
+        driver.set_busy_timeout(Some(10));
+
+        let result = run!(driver.full_refresh());
+
+        assert!(matches!(result, Err(Error::BusyTimeout)));
+    }
+
+    #[test]
+    fn busy_timeout() {
+        let (mut driver, _, busy, _) = driver();
+        busy.stick();
+        driver.set_busy_timeout(Some(10));
+
+        let result = run!(driver.full_refresh());
+
+        assert!(matches!(result, Err(Error::BusyTimeout)));
+    }
+
+    #[test]
+    fn forced_full_refresh_writes_frame_to_red_buffer() {
+        let (mut driver, interface, _, _) = driver();
+        driver.set_refresh_policy(RefreshPolicy {
+            full_refresh_every: Some(1),
+            ..Default::default()
+        });
+        run!(driver.full_update_from_buffer(&[0xFF; 16 * 296])).unwrap();
+        run!(driver.fast_update_from_buffer(&[0x00; 16 * 296])).unwrap();
+        interface.take_transactions();
+        let buffer = [0xAA; 16 * 296];
+
+        run!(driver.fast_update_from_buffer(&buffer)).unwrap();
+
+        assert_eq!(driver.full_refresh_count(), 2);
+        assert_eq!(driver.fast_refresh_count(), 1);
+        let transactions = interface.take_transactions();
+        let refresh = transactions
+            .iter()
+            .position(|transaction| *transaction == Transaction::Command(command::MASTER_ACTIVATE))
+            .unwrap();
+        let red_write = [
+            Transaction::Command(command::WRITE_RED_DATA),
+            Transaction::Data(buffer.to_vec()),
+        ];
+        assert!(transactions[..refresh]
+            .windows(2)
+            .any(|write| write == red_write));
+    }
+
+    #[test]
+    fn no_windows_for_equal_buffers() {
+        let buffer = [0xFF; 8];
+
+        assert_eq!(changed_windows(&buffer, &buffer, 2).next(), None);
+    }
+
+    #[test]
+    fn consecutive_changed_lines_are_combined() {
+        #[rustfmt::skip]
+        let previous = [
+            0xFF, 0xFF, 0xFF,
+            0xFF, 0xFF, 0xFF,
+            0xFF, 0xFF, 0xFF,
+            0xFF, 0xFF, 0xFF,
+            0xFF, 0xFF, 0xFF,
+        ];
+        #[rustfmt::skip]
+        let buffer = [
+            0xFF, 0xFF, 0xFF,
+            0xFF, 0x00, 0xFF,
+            0xFF, 0xFF, 0x00,
+            0xFF, 0xFF, 0xFF,
+            0x00, 0xFF, 0xFF,
+        ];
+
+        let mut windows = changed_windows(&buffer, &previous, 3);
+
+        assert_eq!(windows.next(), Some((0, 1, 24, 4)));
+        assert_eq!(windows.next(), None);
+    }
+
+    #[test]
+    fn distant_changed_lines_get_separate_windows() {
+        let previous = [0xFF; 40];
+        let mut buffer = previous;
+        buffer[0] = 0x00;
+        buffer[39] = 0x00;
+
+        let mut windows = changed_windows(&buffer, &previous, 1);
+
+        assert_eq!(windows.next(), Some((0, 0, 8, 1)));
+        assert_eq!(windows.next(), Some((0, 39, 8, 1)));
+        assert_eq!(windows.next(), None);
+    }
+
+    #[test]
+    fn many_changed_windows_send_the_full_frame() {
+        let (mut reference, reference_interface, _, _) = driver();
+        let (mut driver, interface, _, _) = driver();
+        let mut previous = [0xFF; 16 * 296];
+        let mut buffer = previous;
+        for line in buffer.chunks_mut(16).step_by(34) {
+            line[0] = 0x00;
+        }
+        assert!(changed_windows(&buffer, &previous, 16).count() > MAX_CHANGED_WINDOWS);
+
+        run!(driver.fast_update_diff_from_buffer(&buffer, &mut previous)).unwrap();
+        run!(reference.fast_update_from_buffer(&buffer)).unwrap();
+
+        assert_eq!(
+            interface.take_transactions(),
+            reference_interface.take_transactions()
+        );
+        assert_eq!(previous, buffer);
+    }
+
+    fn emulated_driver<C>(
+        emulator: &Emulator,
+    ) -> DisplayDriver<Emulator, MockBusyPin, MockResetPin, MockDelay, 128, 128, 296, C>
+    where
+        C: ColorType,
+    {
+        let mut driver = DisplayDriver::new(
+            emulator.clone(),
+            MockBusyPin::new(),
+            MockResetPin::new(),
+            MockDelay::new(),
+        );
+        run!(driver.init()).unwrap();
+        driver
+    }
+
+    #[test]
+    fn full_update_is_displayed() {
+        let emulator = Emulator::new();
+        let mut driver = emulated_driver::<Color>(&emulator);
+        let mut buffer = [0xFF; 16 * 296];
+        buffer[16 * 10 + 1] = 0b0100_0000;
+
+        run!(driver.full_update_from_buffer(&buffer)).unwrap();
+
+        assert_eq!(emulator.displayed_color(8, 10), Color::Black);
+        assert_eq!(emulator.displayed_color(9, 10), Color::White);
+        assert_eq!(emulator.displayed_color(10, 10), Color::Black);
+        assert_eq!(emulator.displayed_color(16, 10), Color::White);
+        assert_eq!(emulator.refreshes(), [0xF7]);
+    }
+
+    #[test]
+    fn partial_update_lands_in_window() {
+        let emulator = Emulator::new();
+        let mut driver = emulated_driver::<Color>(&emulator);
+        run!(driver.full_update_from_buffer(&[0xFF; 16 * 296])).unwrap();
+
+        run!(driver.fast_partial_update_from_buffer(&[0x00; 2 * 3], 16, 5, 16, 3)).unwrap();
+
+        for y in 4..9 {
+            for x in 15..33 {
+                let inside = (16..32).contains(&x) && (5..8).contains(&y);
+                let expected = if inside { Color::Black } else { Color::White };
+                assert_eq!(emulator.displayed_color(x, y), expected, "({x}, {y})");
+            }
+        }
+        // The red RAM holds the previous image for the next fast refresh.
+        let (bw_ram, red_ram) = (emulator.bw_ram(), emulator.red_ram());
+        for (bw_line, red_line) in bw_ram.chunks(22).zip(red_ram.chunks(22)) {
+            assert_eq!(bw_line[..16], red_line[..16]);
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn viewport_update_sends_only_viewport() {
+        use crate::graphics::{Display290BlackWhite, DisplayRotation};
+        use embedded_graphics::{
+            draw_target::DrawTarget,
+            geometry::{Point, Size},
+        };
+
+        let emulator = Emulator::new();
+        let mut driver = emulated_driver::<Color>(&emulator);
+        let mut display = Display290BlackWhite::new();
+        display.set_rotation(DisplayRotation::Rotate90);
+        run!(driver.full_update(&display)).unwrap();
+
+        // Drawn outside of the viewport, so it isn't sent.
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(0, 0), Size::new(4, 4)),
+                Color::Black,
+            )
+            .unwrap();
+        let mut viewport = display.viewport(Rectangle::new(Point::new(20, 30), Size::new(6, 4)));
+        viewport.clear(Color::Black).unwrap();
+        run!(driver.fast_update_viewport(&viewport)).unwrap();
+
+        // Rotated by 90 degrees the viewport covers buffer columns 94 to 97 and lines 20 to 25.
+        for y in 0..40 {
+            for x in 90..128 {
+                let inside = (94..98).contains(&x) && (20..26).contains(&y);
+                let expected = if inside { Color::Black } else { Color::White };
+                assert_eq!(emulator.displayed_color(x, y), expected, "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn tri_color_fast_update_keeps_red_hidden_until_full_refresh() {
+        let emulator = Emulator::new();
+        let mut driver = emulated_driver::<TriColor>(&emulator);
+        let mut red_buffer = [0x00; 16 * 296];
+        red_buffer[0] = 0x80;
+        run!(driver.full_update_from_buffer(&[0xFF; 16 * 296], &red_buffer)).unwrap();
+        assert_eq!(emulator.displayed_tri_color(0, 0), TriColor::Red);
+
+        run!(driver.fast_update_from_buffer(&[0x00; 16 * 296])).unwrap();
+        assert_eq!(emulator.displayed_tri_color(0, 0), TriColor::Black);
+
+        run!(driver.full_refresh()).unwrap();
+        assert_eq!(emulator.displayed_tri_color(0, 0), TriColor::Red);
+    }
+
+    #[test]
+    fn init_restores_tri_color_fast_refresh() {
+        let emulator = Emulator::new();
+        let mut driver = emulated_driver::<TriColor>(&emulator);
+        let mut red_buffer = [0x00; 16 * 296];
+        red_buffer[0] = 0x80;
+        run!(driver.write_red_buffer(&red_buffer)).unwrap();
+        run!(driver.write_bw_buffer(&[0xFF; 16 * 296])).unwrap();
+        run!(driver.fast_refresh()).unwrap();
+
+        run!(driver.init()).unwrap();
+        assert_eq!(emulator.lut(), None);
+        run!(driver.fast_refresh()).unwrap();
+
+        assert_eq!(
+            emulator.lut().as_deref(),
+            Some(&Lut::TRI_COLOR_FAST_REFRESH.as_bytes()[..])
+        );
+        // The red RAM is bypassed, so the red pixel shows its B/W bit.
+        assert_eq!(emulator.displayed_tri_color(0, 0), TriColor::White);
+    }
+
+    #[test]
+    fn rotate_180_orientation() {
+        let emulator = Emulator::new();
+        let mut driver = emulated_driver::<Color>(&emulator);
+        run!(driver.set_orientation(Orientation::Rotate180)).unwrap();
+        let mut buffer = [0xFF; 16 * 296];
+        buffer[0] = 0b0111_1110;
+
+        run!(driver.full_update_from_buffer(&buffer)).unwrap();
+
+        assert_eq!(emulator.displayed_color(127, 295), Color::Black);
+        assert_eq!(emulator.displayed_color(126, 295), Color::White);
+        assert_eq!(emulator.displayed_color(120, 295), Color::Black);
+        assert_eq!(emulator.displayed_color(0, 0), Color::White);
+    }
+
+    #[test]
+    fn mirrored_partial_update() {
+        let emulator = Emulator::new();
+        let mut driver = emulated_driver::<Color>(&emulator);
+        run!(driver.full_update_from_buffer(&[0xFF; 16 * 296])).unwrap();
+        run!(driver.set_orientation(Orientation::MirrorHorizontal)).unwrap();
+
+        // The first pixel of each row is black.
+        run!(driver.fast_partial_update_from_buffer(&[0x7F, 0xFF, 0x7F, 0xFF], 8, 2, 16, 2))
+            .unwrap();
+
+        for y in 2..4 {
+            assert_eq!(emulator.displayed_color(119, y), Color::Black);
+            assert_eq!(emulator.displayed_color(118, y), Color::White);
+            assert_eq!(emulator.displayed_color(8, y), Color::White);
+        }
+        assert_eq!(emulator.displayed_color(119, 1), Color::White);
+        assert_eq!(emulator.displayed_color(119, 4), Color::White);
+    }
+
+    #[test]
+    fn wake_up_restores_orientation() {
+        let emulator = Emulator::new();
+        let mut driver = emulated_driver::<Color>(&emulator);
+        run!(driver.full_update_from_buffer(&[0xFF; 16 * 296])).unwrap();
+        run!(driver.set_orientation(Orientation::MirrorVertical)).unwrap();
+
+        run!(driver.sleep()).unwrap();
+        run!(driver.wake_up()).unwrap();
+        run!(driver.fast_partial_update_from_buffer(&[0x00, 0xFF], 0, 10, 8, 2)).unwrap();
+
+        assert_eq!(emulator.displayed_color(0, 285), Color::Black);
+        assert_eq!(emulator.displayed_color(0, 10), Color::White);
+    }
+
+    #[test]
+    fn horizontal_mirroring_needs_full_visible_width() {
+        let interface = MockInterface::new();
+        let mut driver = WeActStudio213BlackWhiteDriver::new(
+            interface.clone(),
+            MockBusyPin::new(),
+            MockResetPin::new(),
+            MockDelay::new(),
+        );
+
+        for orientation in [Orientation::MirrorHorizontal, Orientation::Rotate180] {
+            let result = run!(driver.set_orientation(orientation));
+            assert!(matches!(result, Err(Error::Unsupported)));
+        }
+        assert_eq!(driver.orientation(), Orientation::Normal);
+        assert_eq!(interface.take_transactions(), []);
+
+        run!(driver.set_orientation(Orientation::MirrorVertical)).unwrap();
+        assert_eq!(driver.orientation(), Orientation::MirrorVertical);
+    }
+
+    #[test]
+    fn mirrored_vertical_partial_update() {
+        let emulator = Emulator::new();
+        let mut driver = emulated_driver::<Color>(&emulator);
+        run!(driver.full_update_from_buffer(&[0xFF; 16 * 296])).unwrap();
+        run!(driver.set_orientation(Orientation::MirrorVertical)).unwrap();
+
+        // Only the first row of the window is black.
+        run!(driver.fast_partial_update_from_buffer(&[0x00, 0xFF], 0, 10, 8, 2)).unwrap();
+
+        assert_eq!(emulator.displayed_color(0, 285), Color::Black);
+        assert_eq!(emulator.displayed_color(0, 284), Color::White);
+        assert_eq!(emulator.displayed_color(0, 10), Color::White);
+    }
 }
@@ -0,0 +1,50 @@
+use core::fmt;
+
+use display_interface::DisplayError;
+
+/// Errors returned by the display driver.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Error {
+    /// Communication with the display over the display interface failed.
+    Interface(DisplayError),
+    /// The display stayed busy for longer than the configured busy timeout.
+    BusyTimeout,
+    /// Reading or writing one of the busy or reset pins failed.
+    PinError,
+    /// The requested region is empty or doesn't fit on the display.
+    InvalidRegion,
+    /// The horizontal position or width of a region is not a multiple of 8.
+    UnalignedRegion,
+    /// A buffer doesn't have the expected size.
+    InvalidBufferSize,
+    /// The operation isn't supported by the display controller.
+    Unsupported,
+    /// A waveform LUT has the wrong size or isn't valid.
+    InvalidLut,
+}
+
+impl From<DisplayError> for Error {
+    fn from(value: DisplayError) -> Self {
+        Error::Interface(value)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Interface(error) => write!(f, "display interface error: {error:?}"),
+            Error::BusyTimeout => {
+                f.write_str("timeout while waiting for the display to become idle")
+            }
+            Error::PinError => f.write_str("error while accessing a GPIO pin"),
+            Error::InvalidRegion => f.write_str("invalid display region"),
+            Error::UnalignedRegion => f.write_str("display region is not byte-aligned"),
+            Error::InvalidBufferSize => f.write_str("buffer has an invalid size"),
+            Error::Unsupported => f.write_str("operation not supported by the display controller"),
+            Error::InvalidLut => f.write_str("invalid waveform LUT"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -3,18 +3,30 @@ mod flags {
     pub const DEEP_SLEEP_NORMAL_MODE: u8 = 0b00; // Also called Power on Reset [POR]
     pub const DEEP_SLEEP_MODE_1: u8 = 0b01; // RAM is retained (but not accessible)
     pub const DEEP_SLEEP_MODE_2: u8 = 0b11; // RAM is not retained
+    pub const GATE_SCAN_REVERSED: u8 = 0b001; // TB bit of the driver output control
+    pub const DATA_ENTRY_DECRY_DECRX: u8 = 0b00;
+    pub const DATA_ENTRY_DECRY_INCRX: u8 = 0b01;
+    pub const DATA_ENTRY_INCRY_DECRX: u8 = 0b10;
     pub const DATA_ENTRY_INCRY_INCRX: u8 = 0b11;
     pub const INTERNAL_TEMP_SENSOR: u8 = 0x80;
+    /// Display Update Control 2 (`0x22`) bit that loads the temperature from the selected sensor.
+    pub const LOAD_TEMPERATURE: u8 = 0x20;
     pub const BORDER_WAVEFORM_FOLLOW_LUT: u8 = 0b0100;
     pub const BORDER_WAVEFORM_LUT0: u8 = 0b00;
     pub const BORDER_WAVEFORM_LUT1: u8 = 0b01;
     pub const BORDER_WAVEFORM_LUT2: u8 = 0b10;
     pub const BORDER_WAVEFORM_LUT3: u8 = 0b11;
+    /// Display Update Control 1 (`0x21`) RAM option that ignores the content of the red RAM.
+    pub const BYPASS_RED_RAM: u8 = 0x40;
     pub const DISPLAY_MODE_1: u8 = 0xF7;
+    /// Display mode 1 using the LUT in the register instead of loading one from OTP.
+    pub const DISPLAY_MODE_1_REGISTER_LUT: u8 = 0xC7;
     pub const DISPLAY_MODE_2: u8 = 0xFF;
+    /// Display mode 2 using the LUT in the register instead of loading one from OTP.
+    pub const DISPLAY_MODE_2_REGISTER_LUT: u8 = 0xCF;
     /// Undocumented value for the "Display Update Control 2 (`0x22`) command.
-    /// Together with the [`crate::lut::LUT_PARTIAL_UPDATE`] lut this yields much better looking
-    /// quick refreshes.
+    /// Together with the [`Lut::SSD1680_FAST_REFRESH`](crate::lut::Lut::SSD1680_FAST_REFRESH)
+    /// waveform this yields much better looking quick refreshes.
     pub const UNDOCUMENTED: u8 = 0xCC;
 }
 
@@ -2,12 +2,19 @@ use core::convert::Infallible;
 
 use embedded_graphics::{
     draw_target::DrawTarget,
-    geometry::{OriginDimensions, Point, Size},
+    geometry::{Dimensions, OriginDimensions, Point, Size},
     pixelcolor::PixelColor,
+    primitives::{PointsIter, Rectangle},
+    transform::Transform,
     Pixel,
 };
 
-use crate::color::{Color, ColorType, TriColor};
+use crate::{
+    color::{Color, ColorType, GrayLevel, TriColor},
+    Error, Panel, WeActStudio154, WeActStudio213, WeActStudio290, WeActStudio420,
+};
+
+pub mod dither;
 
 /// Rotation of the display.
 #[derive(Debug, Clone, Copy, Default)]
@@ -23,57 +30,137 @@ pub enum DisplayRotation {
     Rotate270,
 }
 
+/// Mirroring of the display, applied after the rotation.
+///
+/// Useful when the display is viewed through a mirror or mounted upside-down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayMirror {
+    /// No mirroring.
+    #[default]
+    None,
+    /// Mirror left to right.
+    Horizontal,
+    /// Mirror top to bottom.
+    Vertical,
+    /// Mirror both horizontally and vertically.
+    Both,
+}
+
 /// Computes the needed buffer length. Takes care of rounding up in case `width`
 /// is not divisible by 8.
 pub const fn buffer_len<C>(width: usize, height: usize) -> usize
 where
     C: ColorType,
 {
-    (width + 7) / 8 * height * C::BUFFER_COUNT
+    width.div_ceil(8) * height * C::BUFFER_COUNT
 }
 
 /// In-memory display buffer to render to using `embedded-graphics`.
 ///
+/// The buffer is `WIDTH` pixels wide but only the first `VISIBLE_WIDTH` columns are visible on the
+/// screen. The size reported to `embedded-graphics` and the clipping of drawn pixels only cover
+/// the visible area, for every rotation.
+///
 /// `BUFFER_SIZE` can be calculated using [`buffer_len`].
-pub struct Display<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize, C> {
+pub struct Display<
+    const WIDTH: u32,
+    const VISIBLE_WIDTH: u32,
+    const HEIGHT: u32,
+    const BUFFER_SIZE: usize,
+    C,
+> {
     buffer: [u8; BUFFER_SIZE],
     rotation: DisplayRotation,
+    mirror: DisplayMirror,
+    dirty: Option<DirtyArea>,
     _color: core::marker::PhantomData<C>,
 }
 
+/// Bounding box of changed pixels in buffer coordinates. All bounds are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DirtyArea {
+    min_x: u32,
+    min_y: u32,
+    max_x: u32,
+    max_y: u32,
+}
+
+impl DirtyArea {
+    fn including(self, x: u32, y: u32) -> Self {
+        Self {
+            min_x: self.min_x.min(x),
+            min_y: self.min_y.min(y),
+            max_x: self.max_x.max(x),
+            max_y: self.max_y.max(y),
+        }
+    }
+}
+
+/// Display buffer for the given [`Panel`] and color.
+macro_rules! panel_display {
+    ($panel:ty, $color:ty) => {
+        Display<
+            { <$panel>::WIDTH },
+            { <$panel>::VISIBLE_WIDTH },
+            { <$panel>::HEIGHT },
+            { buffer_len::<$color>(<$panel>::WIDTH as usize, <$panel>::HEIGHT as usize) },
+            $color,
+        >
+    };
+}
+
+/// Display buffer for the WeAct Studio 1.54 inch B/W display.
+pub type Display154BlackWhite = panel_display!(WeActStudio154, Color);
 /// Display buffer for the WeAct Studio 2.9 inch B/W display.
-pub type Display290BlackWhite = Display<128, 296, { buffer_len::<Color>(128, 296) }, Color>;
+pub type Display290BlackWhite = panel_display!(WeActStudio290, Color);
 /// Display buffer for the WeAct Studio 2.9 inch tri-color display.
-pub type Display290TriColor = Display<128, 296, { buffer_len::<TriColor>(128, 296) }, TriColor>;
+pub type Display290TriColor = panel_display!(WeActStudio290, TriColor);
 /// Display buffer for the WeAct Studio 2.13 inch B/W display.
 ///
 /// The screen uses a 128 pixel wide buffer but only 122 pixels are visible.
-pub type Display213BlackWhite = Display<128, 250, { buffer_len::<Color>(128, 250) }, Color>;
+pub type Display213BlackWhite = panel_display!(WeActStudio213, Color);
 /// Display buffer for the WeAct Studio 2.13 inch tri-color display.
 ///
 /// The screen uses a 128 pixel wide buffer but only 122 pixels are visible.
-pub type Display213TriColor = Display<128, 250, { buffer_len::<TriColor>(128, 250) }, TriColor>;
+pub type Display213TriColor = panel_display!(WeActStudio213, TriColor);
+/// Display buffer for the WeAct Studio 4.2 inch B/W display.
+pub type Display420BlackWhite = panel_display!(WeActStudio420, Color);
+
+/// Grayscale display buffer for the WeAct Studio 2.13 inch B/W display.
+///
+/// The screen uses a 128 pixel wide buffer but only 122 pixels are visible.
+pub type Display213GrayLevel = panel_display!(WeActStudio213, GrayLevel);
+/// Grayscale display buffer for the WeAct Studio 2.9 inch B/W display.
+pub type Display290GrayLevel = panel_display!(WeActStudio290, GrayLevel);
 
 /// Generically-sized B/W display buffer.
 ///
 /// `WIDTH` must be a multiple of 8. `BUFFER_SIZE` can be calculated using [`buffer_len`].
 pub type DisplayBlackWhite<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize> =
-    Display<WIDTH, HEIGHT, BUFFER_SIZE, Color>;
+    Display<WIDTH, WIDTH, HEIGHT, BUFFER_SIZE, Color>;
 
 /// Generically-sized tri-color display buffer.
 ///
 /// `WIDTH` must be a multiple of 8. `BUFFER_SIZE` can be calculated using [`buffer_len`].
 pub type DisplayTriColor<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize> =
-    Display<WIDTH, HEIGHT, BUFFER_SIZE, TriColor>;
+    Display<WIDTH, WIDTH, HEIGHT, BUFFER_SIZE, TriColor>;
 
-impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
-    Display<WIDTH, HEIGHT, BUFFER_SIZE, Color>
+impl<const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
+    Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, Color>
 {
     /// Creates a new display buffer filled with the default color.
     pub fn new() -> Self {
+        let () = Self::VISIBLE_WIDTH_CHECK;
         Self {
             buffer: [Color::default().byte_value().0; BUFFER_SIZE],
             rotation: Default::default(),
+            mirror: Default::default(),
+            dirty: Some(DirtyArea {
+                min_x: 0,
+                min_y: 0,
+                max_x: WIDTH - 1,
+                max_y: HEIGHT - 1,
+            }),
             _color: core::marker::PhantomData,
         }
     }
@@ -85,23 +172,48 @@ impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
 
     /// Clear the display buffer with the given color.
     pub fn clear(&mut self, color: Color) {
-        self.buffer.fill(color.byte_value().0);
+        self.fill_plane(0, color.byte_value().0);
+    }
+
+    /// Copy a 1 bit per pixel image of `width` by `height` pixels to position `x`, `y` of the
+    /// display.
+    ///
+    /// `data` uses the same layout as `ImageRaw<BinaryColor>`: rows of `width` pixels, each padded
+    /// to a full byte, with the most significant bit first. A set bit is white. The image is
+    /// clipped to the display and the rotation and mirroring are applied.
+    ///
+    /// Unrotated images whose left edge is on a byte boundary are copied byte by byte.
+    ///
+    /// Returns [`Error::InvalidBufferSize`] without drawing anything if `data` is too short for
+    /// the image.
+    pub fn blit(
+        &mut self,
+        data: &[u8],
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> crate::Result<()> {
+        let area = blit_area(&[data], x, y, width, height)?;
+        self.blit_plane(0, data, &area);
+        Ok(())
     }
 }
 
-impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize> Default
-    for Display<WIDTH, HEIGHT, BUFFER_SIZE, Color>
+impl<const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
+    Default for Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, Color>
 {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
-    Display<WIDTH, HEIGHT, BUFFER_SIZE, TriColor>
+impl<const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
+    Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, TriColor>
 {
     /// Creates a new display buffer filled with the default color.
     pub fn new() -> Self {
+        let () = Self::VISIBLE_WIDTH_CHECK;
         let background_color = TriColor::default();
 
         let mut buffer = [0; BUFFER_SIZE];
@@ -111,6 +223,13 @@ impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
         Self {
             buffer,
             rotation: Default::default(),
+            mirror: Default::default(),
+            dirty: Some(DirtyArea {
+                min_x: 0,
+                min_y: 0,
+                max_x: WIDTH - 1,
+                max_y: HEIGHT - 1,
+            }),
             _color: core::marker::PhantomData,
         }
     }
@@ -127,24 +246,115 @@ impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
 
     /// Clear the display buffer with the given color.
     pub fn clear(&mut self, color: TriColor) {
-        self.buffer[..(BUFFER_SIZE / 2)].fill(color.byte_value().0);
-        self.buffer[(BUFFER_SIZE / 2)..].fill(color.byte_value().1);
+        self.fill_plane(0, color.byte_value().0);
+        self.fill_plane(1, color.byte_value().1);
+    }
+
+    /// Copy a tri-color image, given as a B/W and a red 1 bit per pixel plane, into the given area
+    /// of the display.
+    ///
+    /// Both planes use the layout described in [`Display::blit`]. A set bit in the red plane makes
+    /// the pixel red, otherwise a set bit in the B/W plane makes it white.
+    ///
+    /// Returns [`Error::InvalidBufferSize`] without drawing anything if one of the planes is too
+    /// short for the image.
+    pub fn blit(
+        &mut self,
+        bw_data: &[u8],
+        red_data: &[u8],
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> crate::Result<()> {
+        let area = blit_area(&[bw_data, red_data], x, y, width, height)?;
+        self.blit_plane(0, bw_data, &area);
+        self.blit_plane(1, red_data, &area);
+        Ok(())
+    }
+}
+
+impl<const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
+    Default for Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, TriColor>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Generically-sized grayscale display buffer.
+///
+/// `WIDTH` must be a multiple of 8. `BUFFER_SIZE` can be calculated using [`buffer_len`].
+pub type DisplayGrayLevel<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize> =
+    Display<WIDTH, WIDTH, HEIGHT, BUFFER_SIZE, GrayLevel>;
+
+impl<const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
+    Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, GrayLevel>
+{
+    /// Creates a new display buffer filled with the default color.
+    pub fn new() -> Self {
+        let () = Self::VISIBLE_WIDTH_CHECK;
+        let background_color = GrayLevel::default();
+
+        let mut buffer = [0; BUFFER_SIZE];
+        buffer[..(BUFFER_SIZE / 2)].fill(background_color.byte_value().0);
+        buffer[(BUFFER_SIZE / 2)..].fill(background_color.byte_value().1);
+
+        Self {
+            buffer,
+            rotation: Default::default(),
+            mirror: Default::default(),
+            dirty: Some(DirtyArea {
+                min_x: 0,
+                min_y: 0,
+                max_x: WIDTH - 1,
+                max_y: HEIGHT - 1,
+            }),
+            _color: core::marker::PhantomData,
+        }
+    }
+
+    /// Get the internal B/W buffer, which holds the high bit of every gray level.
+    pub fn bw_buffer(&self) -> &[u8] {
+        &self.buffer[..(BUFFER_SIZE / 2)]
+    }
+
+    /// Get the internal red buffer, which holds the low bit of every gray level.
+    pub fn red_buffer(&self) -> &[u8] {
+        &self.buffer[(BUFFER_SIZE / 2)..]
+    }
+
+    /// Clear the display buffer with the given color.
+    pub fn clear(&mut self, color: GrayLevel) {
+        self.fill_plane(0, color.byte_value().0);
+        self.fill_plane(1, color.byte_value().1);
     }
 }
 
-impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize> Default
-    for Display<WIDTH, HEIGHT, BUFFER_SIZE, TriColor>
+impl<const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
+    Default for Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, GrayLevel>
 {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize, C>
-    Display<WIDTH, HEIGHT, BUFFER_SIZE, C>
+impl<
+        const WIDTH: u32,
+        const VISIBLE_WIDTH: u32,
+        const HEIGHT: u32,
+        const BUFFER_SIZE: usize,
+        C,
+    > Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>
 where
     C: ColorType + PixelColor,
 {
+    /// Fails compilation if the visible width is larger than the buffer.
+    const VISIBLE_WIDTH_CHECK: () = assert!(
+        VISIBLE_WIDTH <= WIDTH,
+        "visible width is larger than the buffer width"
+    );
+
     /// Get the current rotation of the display.
     pub fn rotation(&self) -> DisplayRotation {
         self.rotation
@@ -155,45 +365,387 @@ where
         self.rotation = rotation;
     }
 
+    /// Get the current mirroring of the display.
+    pub fn mirror(&self) -> DisplayMirror {
+        self.mirror
+    }
+
+    /// Sets the mirroring of the display.
+    ///
+    /// Like the rotation this only affects pixels drawn afterwards. The driver can also mirror
+    /// vertically in hardware with [`DisplayDriver::set_gate_scan_reversed`](crate::DisplayDriver::set_gate_scan_reversed).
+    pub fn set_mirror(&mut self, mirror: DisplayMirror) {
+        self.mirror = mirror;
+    }
+
+    /// Get the color of the pixel at the given point, taking the rotation and mirroring into
+    /// account.
+    ///
+    /// Returns `None` if the point is outside the display.
+    pub fn get_pixel(&self, point: Point) -> Option<C> {
+        if outside_display(point, VISIBLE_WIDTH, HEIGHT, self.rotation) {
+            return None;
+        }
+        let (x, y) = self.buffer_position(point);
+        let (index, bit) = pixel_position_in_buffer(x, y, WIDTH);
+        let (bw_byte, red_byte) = self.pixel_bytes(index as usize);
+        Some(C::from_bit_values(
+            u8::from(bw_byte & bit != 0),
+            u8::from(red_byte & bit != 0),
+        ))
+    }
+
+    /// Get the color of the pixel at the given buffer position, ignoring the rotation.
+    #[cfg(feature = "simulator")]
+    pub(crate) fn buffer_pixel(&self, x: u32, y: u32) -> C {
+        let index = (x / 8 + bytes_per_line(WIDTH) * y) as usize;
+        let bit = 0x80 >> (x % 8);
+        let (bw_byte, red_byte) = self.pixel_bytes(index);
+        C::from_bit_values(u8::from(bw_byte & bit != 0), u8::from(red_byte & bit != 0))
+    }
+
+    /// Borrow a part of the display as a separate draw target.
+    ///
+    /// `area` is in display coordinates, i.e. with the rotation applied, and is clipped to the
+    /// display. The returned [`Viewport`] uses coordinates relative to the top-left corner of
+    /// `area` and clips everything drawn to it.
+    pub fn viewport(
+        &mut self,
+        area: Rectangle,
+    ) -> Viewport<'_, WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C> {
+        let area = area.intersection(&self.bounding_box());
+        Viewport {
+            display: self,
+            area,
+        }
+    }
+
+    /// Get the area that changed since the display was created or [`Self::clear_dirty`] was last
+    /// called.
+    ///
+    /// The area is in buffer coordinates, i.e. without rotation, and is extended to byte boundaries
+    /// horizontally so it can be sent to the display directly. Returns `None` if nothing changed.
+    pub fn dirty_area(&self) -> Option<Rectangle> {
+        self.dirty.map(|area| {
+            let min_x = area.min_x / 8 * 8;
+            let max_x = area.max_x / 8 * 8 + 7;
+            Rectangle::with_corners(
+                Point::new(min_x as i32, area.min_y as i32),
+                Point::new(max_x as i32, area.max_y as i32),
+            )
+        })
+    }
+
+    /// Mark the whole display as unchanged.
+    ///
+    /// Call this after the changes have been sent to the display.
+    pub fn clear_dirty(&mut self) {
+        self.dirty = None;
+    }
+
+    /// Mark the pixel at the given buffer position as changed.
+    fn mark_dirty(&mut self, x: u32, y: u32) {
+        self.dirty = Some(match self.dirty {
+            Some(area) => area.including(x, y),
+            None => DirtyArea {
+                min_x: x,
+                min_y: y,
+                max_x: x,
+                max_y: y,
+            },
+        });
+    }
+
+    /// Bytes at `index` in the B/W and, if used, the red buffer.
+    fn pixel_bytes(&self, index: usize) -> (u8, u8) {
+        if C::BUFFER_COUNT == 2 {
+            (self.buffer[index], self.buffer[index + BUFFER_SIZE / 2])
+        } else {
+            (self.buffer[index], 0)
+        }
+    }
+
+    /// Fill one of the single color buffers with `value`, keeping track of the changed bytes.
+    fn fill_plane(&mut self, plane: usize, value: u8) {
+        let plane_size = BUFFER_SIZE / C::BUFFER_COUNT;
+        let bytes_per_line = bytes_per_line(WIDTH) as usize;
+        for index in 0..plane_size {
+            let byte = &mut self.buffer[plane * plane_size + index];
+            if *byte != value {
+                *byte = value;
+                let x = (index % bytes_per_line * 8) as u32;
+                let y = (index / bytes_per_line) as u32;
+                self.mark_dirty(x, y);
+            }
+        }
+    }
+
+    /// Copy one plane of a 1 bit per pixel image into the single color buffer `plane`.
+    fn blit_plane(&mut self, plane: usize, data: &[u8], area: &Rectangle) {
+        let bytes_per_row = area.size.width.div_ceil(8) as usize;
+
+        let clipped = area.intersection(&self.bounding_box());
+        if clipped.is_zero_sized() {
+            return;
+        }
+        let offset = clipped.top_left - area.top_left;
+        let (offset_x, offset_y) = (offset.x as usize, offset.y as usize);
+
+        if matches!(self.rotation, DisplayRotation::Rotate0) && self.mirror == DisplayMirror::None {
+            // Image rows are buffer lines, copy them a byte at a time.
+            let x = clipped.top_left.x as u32;
+            for row in 0..clipped.size.height {
+                let y = clipped.top_left.y as u32 + row;
+                let start = (offset_y + row as usize) * bytes_per_row;
+                let source = &data[start..start + bytes_per_row];
+                if self.copy_bits(plane, source, offset_x, x, y, clipped.size.width) {
+                    self.mark_dirty(x, y);
+                    self.mark_dirty(x + clipped.size.width - 1, y);
+                }
+            }
+        } else {
+            for point in clipped.points() {
+                let image = point - area.top_left;
+                let (image_x, image_y) = (image.x as usize, image.y as usize);
+                let byte = data[image_y * bytes_per_row + image_x / 8];
+                let value = if byte & (0x80 >> (image_x % 8)) != 0 {
+                    0xFF
+                } else {
+                    0x00
+                };
+                let (x, y) = self.buffer_position(point);
+                let (index, bit) = pixel_position_in_buffer(x, y, WIDTH);
+                if self.write_plane_bits(plane, index as usize, bit, value) {
+                    self.mark_dirty(x, y);
+                }
+            }
+        }
+    }
+
+    /// Copy `count` bits starting at bit `source_x` of `source` to the buffer line `y` of `plane`,
+    /// starting at column `x`.
+    ///
+    /// Whole bytes are copied directly when source and destination are byte aligned, otherwise the
+    /// source bits are shifted into place. Returns whether the buffer changed.
+    fn copy_bits(
+        &mut self,
+        plane: usize,
+        source: &[u8],
+        source_x: usize,
+        x: u32,
+        y: u32,
+        count: u32,
+    ) -> bool {
+        let line = plane * (BUFFER_SIZE / C::BUFFER_COUNT) + (bytes_per_line(WIDTH) * y) as usize;
+        let mut changed = false;
+        let mut copied = 0;
+
+        if source_x.is_multiple_of(8) && x.is_multiple_of(8) {
+            let bytes = (count / 8) as usize;
+            let source = &source[source_x / 8..source_x / 8 + bytes];
+            let start = line + (x / 8) as usize;
+            let destination = &mut self.buffer[start..start + bytes];
+            if destination != source {
+                destination.copy_from_slice(source);
+                changed = true;
+            }
+            copied = bytes as u32 * 8;
+        }
+
+        while copied < count {
+            let column = x + copied;
+            let bit = column % 8;
+            let bits = (8 - bit).min(count - copied);
+            let value = source_byte(source, source_x + copied as usize) >> bit;
+            let mask = (0xFF >> bit) & (0xFF << (8 - bit - bits));
+            let index = line + (column / 8) as usize;
+            let previous = self.buffer[index];
+            self.buffer[index] = (previous & !mask) | (value & mask);
+            changed |= self.buffer[index] != previous;
+            copied += bits;
+        }
+
+        changed
+    }
+
+    /// Set the bits selected by `mask` in the byte at `index` of the single color buffer `plane`
+    /// to the bits in `value`. Returns whether the byte changed.
+    fn write_plane_bits(&mut self, plane: usize, index: usize, mask: u8, value: u8) -> bool {
+        let byte = &mut self.buffer[plane * (BUFFER_SIZE / C::BUFFER_COUNT) + index];
+        let previous = *byte;
+        *byte = (previous & !mask) | (value & mask);
+        *byte != previous
+    }
+
+    /// Position in the buffer of a point on the display.
+    fn buffer_position(&self, point: Point) -> (u32, u32) {
+        let (x, y) = find_mirror(
+            point.x as u32,
+            point.y as u32,
+            VISIBLE_WIDTH,
+            HEIGHT,
+            self.rotation,
+            self.mirror,
+        );
+        find_rotation(x, y, VISIBLE_WIDTH, HEIGHT, self.rotation)
+    }
+
     fn set_pixel(&mut self, pixel: Pixel<C>) {
-        // let rotation = self.rotation;
         let Pixel(point, color) = pixel;
-        let Point { x, y } = point;
 
-        if outside_display(point, WIDTH, HEIGHT, self.rotation) {
+        if outside_display(point, VISIBLE_WIDTH, HEIGHT, self.rotation) {
             return;
         }
 
-        let (index, bit) =
-            pixel_position_in_buffer(x as u32, y as u32, WIDTH, HEIGHT, self.rotation);
-        let index = index as usize;
+        let (x, y) = self.buffer_position(point);
+        let (index, bit) = pixel_position_in_buffer(x, y, WIDTH);
+        if self.write_bits(index as usize, bit, color) {
+            self.mark_dirty(x, y);
+        }
+    }
+
+    /// Set the bits selected by `mask` in the byte at `index` of each buffer to `color`.
+    ///
+    /// Returns whether any of the bytes changed.
+    fn write_bits(&mut self, index: usize, mask: u8, color: C) -> bool {
         let (bw_bit, red_bit) = color.bit_value();
+        let previous = self.pixel_bytes(index);
 
         #[allow(clippy::collapsible_else_if)]
         if C::BUFFER_COUNT == 2 {
-            if red_bit == 1 {
+            if red_bit == 1 && C::SECOND_BUFFER_HIDES_FIRST {
                 // Red buffer takes precendence over B/W buffer so no need to update B/W buffer.
-                self.buffer[index + BUFFER_SIZE / 2] |= bit;
+                self.buffer[index + BUFFER_SIZE / 2] |= mask;
             } else {
                 if bw_bit == 1 {
-                    self.buffer[index] |= bit;
+                    self.buffer[index] |= mask;
+                } else {
+                    self.buffer[index] &= !mask;
+                }
+                if red_bit == 1 {
+                    self.buffer[index + BUFFER_SIZE / 2] |= mask;
                 } else {
-                    self.buffer[index] &= !bit;
+                    self.buffer[index + BUFFER_SIZE / 2] &= !mask;
                 }
-                self.buffer[index + BUFFER_SIZE / 2] &= !bit;
             }
         } else {
             if bw_bit == 1 {
-                self.buffer[index] |= bit;
+                self.buffer[index] |= mask;
             } else {
-                self.buffer[index] &= !bit;
+                self.buffer[index] &= !mask;
+            }
+        }
+
+        self.pixel_bytes(index) != previous
+    }
+
+    /// Fill a rectangle on the display with a single color.
+    ///
+    /// The rectangle is clipped to the display and mapped to the buffer as a whole, so every
+    /// buffer line is written a byte at a time instead of pixel by pixel.
+    fn fill_area(&mut self, area: &Rectangle, color: C) {
+        let area = area.intersection(&self.bounding_box());
+        let Some(bottom_right) = area.bottom_right() else {
+            return;
+        };
+        let (x0, y0) = self.buffer_position(area.top_left);
+        let (x1, y1) = self.buffer_position(bottom_right);
+        let (min_x, max_x) = (x0.min(x1), x0.max(x1));
+        let (min_y, max_y) = (y0.min(y1), y0.max(y1));
+
+        let (first_byte, last_byte) = (min_x / 8, max_x / 8);
+        let first_mask = 0xFF >> (min_x % 8);
+        let last_mask = 0xFF << (7 - max_x % 8);
+        for y in min_y..=max_y {
+            let line = bytes_per_line(WIDTH) * y;
+            let mut changed = false;
+            for byte in first_byte..=last_byte {
+                let mut mask = 0xFF;
+                if byte == first_byte {
+                    mask &= first_mask;
+                }
+                if byte == last_byte {
+                    mask &= last_mask;
+                }
+                changed |= self.write_bits((line + byte) as usize, mask, color);
+            }
+            if changed {
+                self.mark_dirty(min_x, y);
+                self.mark_dirty(max_x, y);
+            }
+        }
+    }
+
+    /// Fill a rectangle on the display with colors given in row-major order.
+    ///
+    /// The buffer position is mapped once per row and then stepped along the row, so every pixel
+    /// is a single write to the buffer.
+    //
+    // Filling a 2.9" display with stripes of alternating colors takes about 160 µs on x86_64 in
+    // release builds for any stripe width, compared to about 350 µs with `draw_iter`. Writing
+    // runs of the same color with `fill_area` took between 960 µs (1 pixel) and 210 µs (32 pixels).
+    fn fill_area_contiguous<I>(&mut self, area: &Rectangle, colors: I)
+    where
+        I: IntoIterator<Item = C>,
+    {
+        let clipped = area.intersection(&self.bounding_box());
+        if clipped.is_zero_sized() {
+            return;
+        }
+        let mut colors = colors.into_iter();
+        let skip_left = (clipped.top_left.x - area.top_left.x) as usize;
+        let skip_right = area.size.width as usize - skip_left - clipped.size.width as usize;
+        let skip_top = (clipped.top_left.y - area.top_left.y) as usize;
+
+        // Stepping one pixel to the right on the display moves by the same amount in the buffer
+        // everywhere on the display.
+        let origin = self.buffer_position(clipped.top_left);
+        let (step_x, step_y) = if clipped.size.width > 1 {
+            let next = self.buffer_position(clipped.top_left + Point::new(1, 0));
+            (
+                next.0 as i32 - origin.0 as i32,
+                next.1 as i32 - origin.1 as i32,
+            )
+        } else {
+            (0, 0)
+        };
+
+        if skip_top > 0
+            && colors
+                .nth(skip_top * area.size.width as usize - 1)
+                .is_none()
+        {
+            return;
+        }
+        for row in 0..clipped.size.height as i32 {
+            if skip_left > 0 && colors.nth(skip_left - 1).is_none() {
+                return;
+            }
+            let (first_x, first_y) = self.buffer_position(clipped.top_left + Point::new(0, row));
+            let (mut x, mut y) = (first_x as i32, first_y as i32);
+            let mut changed = false;
+            for _ in 0..clipped.size.width {
+                let Some(color) = colors.next() else {
+                    break;
+                };
+                let (index, bit) = pixel_position_in_buffer(x as u32, y as u32, WIDTH);
+                changed |= self.write_bits(index as usize, bit, color);
+                x += step_x;
+                y += step_y;
+            }
+            if changed {
+                self.mark_dirty(first_x, first_y);
+                self.mark_dirty((x - step_x) as u32, (y - step_y) as u32);
+            }
+            if skip_right > 0 && colors.nth(skip_right - 1).is_none() {
+                return;
             }
         }
     }
 }
 
-impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize> DrawTarget
-    for Display<WIDTH, HEIGHT, BUFFER_SIZE, Color>
+impl<const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
+    DrawTarget for Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, Color>
 {
     type Color = Color;
     type Error = Infallible;
@@ -208,14 +760,27 @@ impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize> DrawTarget
         Ok(())
     }
 
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.fill_area_contiguous(area, colors);
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_area(area, color);
+        Ok(())
+    }
+
     fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
         self.clear(color);
         Ok(())
     }
 }
 
-impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize> DrawTarget
-    for Display<WIDTH, HEIGHT, BUFFER_SIZE, TriColor>
+impl<const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
+    DrawTarget for Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, TriColor>
 {
     type Color = TriColor;
     type Error = Infallible;
@@ -230,12 +795,251 @@ impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize> DrawTarget
         Ok(())
     }
 
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.fill_area_contiguous(area, colors);
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_area(area, color);
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.clear(color);
+        Ok(())
+    }
+}
+
+impl<const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize>
+    DrawTarget for Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, GrayLevel>
+{
+    type Color = GrayLevel;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for p in pixels.into_iter() {
+            self.set_pixel(p);
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.fill_area_contiguous(area, colors);
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_area(area, color);
+        Ok(())
+    }
+
     fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
         self.clear(color);
         Ok(())
     }
 }
 
+/// A part of a [`Display`] that can be drawn to with coordinates relative to its top-left corner.
+///
+/// Created with [`Display::viewport`]. Drawing to a viewport changes the parent display, and
+/// the driver can send just the part of the screen covered by the viewport, for example with
+/// [`DisplayDriver::fast_update_viewport`](crate::DisplayDriver::fast_update_viewport).
+pub struct Viewport<
+    'a,
+    const WIDTH: u32,
+    const VISIBLE_WIDTH: u32,
+    const HEIGHT: u32,
+    const BUFFER_SIZE: usize,
+    C,
+> {
+    display: &'a mut Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>,
+    area: Rectangle,
+}
+
+impl<
+        const WIDTH: u32,
+        const VISIBLE_WIDTH: u32,
+        const HEIGHT: u32,
+        const BUFFER_SIZE: usize,
+        C,
+    > Viewport<'_, WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>
+where
+    C: ColorType + PixelColor,
+{
+    /// Get the area of the parent display covered by the viewport, in display coordinates.
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    /// Get the parent display.
+    pub fn display(&self) -> &Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C> {
+        self.display
+    }
+
+    /// Get the area of the buffer covered by the viewport.
+    ///
+    /// The area is in buffer coordinates, i.e. without rotation and mirroring, and is extended to
+    /// byte boundaries horizontally so it can be sent to the display directly. Returns `None` if
+    /// the viewport is empty.
+    pub fn buffer_area(&self) -> Option<Rectangle> {
+        let bottom_right = self.area.bottom_right()?;
+        let (x0, y0) = self.display.buffer_position(self.area.top_left);
+        let (x1, y1) = self.display.buffer_position(bottom_right);
+        let min_x = x0.min(x1) / 8 * 8;
+        let max_x = x0.max(x1) / 8 * 8 + 7;
+        Some(Rectangle::with_corners(
+            Point::new(min_x as i32, y0.min(y1) as i32),
+            Point::new(max_x as i32, y0.max(y1) as i32),
+        ))
+    }
+}
+
+impl<
+        const WIDTH: u32,
+        const VISIBLE_WIDTH: u32,
+        const HEIGHT: u32,
+        const BUFFER_SIZE: usize,
+        C,
+    > DrawTarget for Viewport<'_, WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>
+where
+    C: ColorType + PixelColor,
+{
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounding_box = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            if bounding_box.contains(point) {
+                self.display
+                    .set_pixel(Pixel(point + self.area.top_left, color));
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        if self.bounding_box().intersection(area) == *area {
+            self.display
+                .fill_area_contiguous(&area.translate(self.area.top_left), colors);
+            Ok(())
+        } else {
+            self.draw_iter(
+                area.points()
+                    .zip(colors)
+                    .map(|(point, color)| Pixel(point, color)),
+            )
+        }
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        self.display
+            .fill_area(&area.translate(self.area.top_left), color);
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.fill_area(&self.area, color);
+        Ok(())
+    }
+}
+
+impl<
+        const WIDTH: u32,
+        const VISIBLE_WIDTH: u32,
+        const HEIGHT: u32,
+        const BUFFER_SIZE: usize,
+        C,
+    > OriginDimensions for Viewport<'_, WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>
+{
+    fn size(&self) -> Size {
+        self.area.size
+    }
+}
+
+/// Draw target adapter that swaps black and white, e.g. to draw images that are stored as
+/// negatives.
+///
+/// Red stays red and gray levels are mirrored. Combine it with
+/// [`DrawTargetExt::color_converted`](embedded_graphics::draw_target::DrawTargetExt::color_converted)
+/// to invert images in other color formats.
+pub struct Inverted<'a, D> {
+    target: &'a mut D,
+}
+
+impl<'a, D> Inverted<'a, D>
+where
+    D: DrawTarget,
+    D::Color: ColorType,
+{
+    /// Wrap `target` to draw to it with inverted colors.
+    pub fn new(target: &'a mut D) -> Self {
+        Self { target }
+    }
+}
+
+impl<D> Dimensions for Inverted<'_, D>
+where
+    D: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D> DrawTarget for Inverted<'_, D>
+where
+    D: DrawTarget,
+    D::Color: ColorType,
+{
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point, color.inverted())),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.target
+            .fill_contiguous(area, colors.into_iter().map(|color| color.inverted()))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.fill_solid(area, color.inverted())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.clear(color.inverted())
+    }
+}
+
 impl PixelColor for Color {
     type Raw = ();
 }
@@ -244,16 +1048,29 @@ impl PixelColor for TriColor {
     type Raw = ();
 }
 
-impl<const WIDTH: u32, const HEIGHT: u32, const BUFFER_SIZE: usize, C> OriginDimensions
-    for Display<WIDTH, HEIGHT, BUFFER_SIZE, C>
+impl PixelColor for GrayLevel {
+    type Raw = ();
+}
+
+impl<
+        const WIDTH: u32,
+        const VISIBLE_WIDTH: u32,
+        const HEIGHT: u32,
+        const BUFFER_SIZE: usize,
+        C,
+    > OriginDimensions for Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>
 where
     C: PixelColor + ColorType,
 {
     fn size(&self) -> Size {
         //if display is rotated 90 deg or 270 then swap height and width
         match self.rotation() {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => Size::new(WIDTH, HEIGHT),
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => Size::new(HEIGHT, WIDTH),
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                Size::new(VISIBLE_WIDTH, HEIGHT)
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                Size::new(HEIGHT, VISIBLE_WIDTH)
+            }
         }
     }
 }
@@ -270,18 +1087,33 @@ fn outside_display(p: Point, width: u32, height: u32, rotation: DisplayRotation)
     }
 }
 
-/// Returns the position of the pixel in the (single color) buffer.
+/// Returns the position of the pixel at buffer position `x`, `y` in the (single color) buffer.
 ///
 /// Return type is (byte index, bit)
-fn pixel_position_in_buffer(
+fn pixel_position_in_buffer(x: u32, y: u32, width: u32) -> (u32, u8) {
+    (x / 8 + bytes_per_line(width) * y, 0x80 >> (x % 8))
+}
+
+/// Mirrors a position on the rotated display. `width` and `height` are the unrotated display
+/// dimensions.
+fn find_mirror(
     x: u32,
     y: u32,
     width: u32,
     height: u32,
     rotation: DisplayRotation,
-) -> (u32, u8) {
-    let (nx, ny) = find_rotation(x, y, width, height, rotation);
-    (nx / 8 + bytes_per_line(width) * ny, 0x80 >> (nx % 8))
+    mirror: DisplayMirror,
+) -> (u32, u32) {
+    let (width, height) = match rotation {
+        DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (width, height),
+        DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (height, width),
+    };
+    match mirror {
+        DisplayMirror::None => (x, y),
+        DisplayMirror::Horizontal => (width - 1 - x, y),
+        DisplayMirror::Vertical => (x, height - 1 - y),
+        DisplayMirror::Both => (width - 1 - x, height - 1 - y),
+    }
 }
 
 fn find_rotation(x: u32, y: u32, width: u32, height: u32, rotation: DisplayRotation) -> (u32, u32) {
@@ -293,8 +1125,49 @@ fn find_rotation(x: u32, y: u32, width: u32, height: u32, rotation: DisplayRotat
     }
 }
 
+/// Inverse of [`find_rotation`], returns the position on the rotated display of a position in the
+/// buffer.
+#[cfg(feature = "simulator")]
+pub(crate) fn rotated_position(
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    rotation: DisplayRotation,
+) -> (u32, u32) {
+    match rotation {
+        DisplayRotation::Rotate0 => (x, y),
+        DisplayRotation::Rotate90 => (y, width - 1 - x),
+        DisplayRotation::Rotate180 => (width - 1 - x, height - 1 - y),
+        DisplayRotation::Rotate270 => (height - 1 - y, x),
+    }
+}
+
+/// The 8 bits of `data` starting at bit `bit`, most significant bit first. Bits past the end of
+/// `data` are zero.
+fn source_byte(data: &[u8], bit: usize) -> u8 {
+    let byte = |index: usize| u16::from(data.get(index).copied().unwrap_or(0));
+    let word = (byte(bit / 8) << 8) | byte(bit / 8 + 1);
+    (word << (bit % 8) >> 8) as u8
+}
+
 const fn bytes_per_line(width: u32) -> u32 {
-    (width + 7) / 8
+    width.div_ceil(8)
+}
+
+/// Area of a blitted image after checking that every plane holds the whole image.
+fn blit_area(
+    planes: &[&[u8]],
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> crate::Result<Rectangle> {
+    let len = bytes_per_line(width) as usize * height as usize;
+    if planes.iter().any(|plane| plane.len() < len) {
+        return Err(Error::InvalidBufferSize);
+    }
+    Ok(Rectangle::new(Point::new(x, y), Size::new(width, height)))
 }
 
 #[cfg(test)]
@@ -303,7 +1176,7 @@ mod tests {
 
     #[test]
     fn pixels_are_set_correctly_in_both_buffers_when_creating_new_tri_color_display() {
-        let display = Display::<8, 1, 2, TriColor>::new();
+        let display = Display::<8, 8, 1, 2, TriColor>::new();
         assert_eq!(display.buffer.len(), 2);
 
         assert_eq!(
@@ -318,7 +1191,7 @@ mod tests {
 
     #[test]
     fn pixel_is_set_in_bw_buffer_when_drawing_black() {
-        let mut display = Display::<8, 1, 2, TriColor>::new();
+        let mut display = Display::<8, 8, 1, 2, TriColor>::new();
 
         display.set_pixel(Pixel(Point::new(0, 0), TriColor::Black));
 
@@ -334,7 +1207,7 @@ mod tests {
 
     #[test]
     fn pixel_is_set_in_both_buffers_when_drawing_red() {
-        let mut display = Display::<8, 1, 2, TriColor>::new();
+        let mut display = Display::<8, 8, 1, 2, TriColor>::new();
 
         display.set_pixel(Pixel(Point::new(0, 0), TriColor::Red));
 
@@ -350,7 +1223,7 @@ mod tests {
 
     #[test]
     fn pixel_is_set_in_both_buffers_when_drawing_red_then_black() {
-        let mut display = Display::<8, 1, 2, TriColor>::new();
+        let mut display = Display::<8, 8, 1, 2, TriColor>::new();
 
         display.set_pixel(Pixel(Point::new(0, 0), TriColor::Red));
         display.set_pixel(Pixel(Point::new(0, 0), TriColor::Black));
@@ -367,7 +1240,7 @@ mod tests {
 
     #[test]
     fn pixel_is_set_in_both_buffers_when_drawing_red_black_red() {
-        let mut display = Display::<8, 1, 2, TriColor>::new();
+        let mut display = Display::<8, 8, 1, 2, TriColor>::new();
 
         display.set_pixel(Pixel(Point::new(0, 0), TriColor::Red));
         display.set_pixel(Pixel(Point::new(0, 0), TriColor::Black));
@@ -385,7 +1258,7 @@ mod tests {
 
     #[test]
     fn clear_sets_both_buffers() {
-        let mut display = Display::<8, 1, 2, TriColor>::new();
+        let mut display = Display::<8, 8, 1, 2, TriColor>::new();
 
         display.set_pixel(Pixel(Point::new(0, 0), TriColor::Black));
         display.set_pixel(Pixel(Point::new(0, 0), TriColor::Red));
@@ -410,4 +1283,539 @@ mod tests {
             "Red buffer has incorrect value"
         );
     }
+
+    #[test]
+    fn get_pixel_uses_rotation() {
+        let mut display = Display290TriColor::new();
+        display.set_rotation(DisplayRotation::Rotate90);
+        display.set_pixel(Pixel(Point::new(3, 5), TriColor::Red));
+        display.set_pixel(Pixel(Point::new(4, 5), TriColor::Black));
+
+        assert_eq!(display.get_pixel(Point::new(3, 5)), Some(TriColor::Red));
+        assert_eq!(display.get_pixel(Point::new(4, 5)), Some(TriColor::Black));
+        assert_eq!(display.get_pixel(Point::new(5, 5)), Some(TriColor::White));
+        assert_eq!(display.get_pixel(Point::new(296, 5)), None);
+    }
+
+    #[test]
+    fn size_is_visible_area() {
+        let mut display = Display213BlackWhite::new();
+        assert_eq!(display.size(), Size::new(122, 250));
+
+        display.set_rotation(DisplayRotation::Rotate90);
+        assert_eq!(display.size(), Size::new(250, 122));
+    }
+
+    #[test]
+    fn rotated_pixels_are_in_visible_columns() {
+        let mut display = Display213BlackWhite::new();
+        let bytes_per_line = 16;
+
+        display.set_rotation(DisplayRotation::Rotate180);
+        display.set_pixel(Pixel(Point::new(0, 0), Color::Black));
+        // Column 121 is the last visible one.
+        assert_eq!(display.buffer[249 * bytes_per_line + 15], 0b1011_1111);
+
+        display.set_rotation(DisplayRotation::Rotate90);
+        display.set_pixel(Pixel(Point::new(0, 0), Color::Black));
+        assert_eq!(display.buffer[15], 0b1011_1111);
+
+        display.set_rotation(DisplayRotation::Rotate270);
+        display.set_pixel(Pixel(Point::new(0, 121), Color::Black));
+        assert_eq!(display.buffer[249 * bytes_per_line + 15], 0b1011_1111);
+    }
+
+    #[test]
+    fn pixels_outside_visible_area_are_clipped() {
+        let mut display = Display213BlackWhite::new();
+        display.clear_dirty();
+
+        display.set_pixel(Pixel(Point::new(122, 0), Color::Black));
+
+        assert_eq!(display.get_pixel(Point::new(122, 0)), None);
+        assert_eq!(display.dirty_area(), None);
+    }
+
+    #[test]
+    fn mirroring_is_applied_after_rotation() {
+        let mut display = Display::<16, 16, 8, 16, Color>::new();
+        display.set_rotation(DisplayRotation::Rotate90);
+        display.set_mirror(DisplayMirror::Horizontal);
+
+        // The rotated display is 8 wide and 16 high, (7, 0) is mirrored to (0, 0) which is
+        // rotated to (15, 0) in the buffer.
+        display.set_pixel(Pixel(Point::new(7, 0), Color::Black));
+        assert_eq!(display.buffer[1], 0b1111_1110);
+        assert_eq!(display.get_pixel(Point::new(7, 0)), Some(Color::Black));
+
+        // (0, 0) is mirrored to (0, 15) which is rotated to (0, 0) in the buffer.
+        display.set_mirror(DisplayMirror::Vertical);
+        display.set_pixel(Pixel(Point::new(0, 0), Color::Black));
+        assert_eq!(display.buffer[0], 0b0111_1111);
+    }
+
+    #[test]
+    fn new_display_is_dirty() {
+        let display = Display::<16, 16, 4, 8, Color>::new();
+
+        assert_eq!(
+            display.dirty_area(),
+            Some(Rectangle::new(Point::zero(), Size::new(16, 4)))
+        );
+    }
+
+    #[test]
+    fn dirty_area_is_byte_aligned_in_buffer_coordinates() {
+        let mut display = Display::<24, 24, 8, 24, Color>::new();
+        display.clear_dirty();
+
+        display.set_pixel(Pixel(Point::new(10, 2), Color::Black));
+        assert_eq!(
+            display.dirty_area(),
+            Some(Rectangle::new(Point::new(8, 2), Size::new(8, 1)))
+        );
+
+        display.set_pixel(Pixel(Point::new(17, 5), Color::Black));
+        assert_eq!(
+            display.dirty_area(),
+            Some(Rectangle::new(Point::new(8, 2), Size::new(16, 4)))
+        );
+
+        display.clear_dirty();
+        display.set_rotation(DisplayRotation::Rotate90);
+        display.set_pixel(Pixel(Point::new(0, 0), Color::Black));
+        assert_eq!(
+            display.dirty_area(),
+            Some(Rectangle::new(Point::new(16, 0), Size::new(8, 1)))
+        );
+    }
+
+    #[test]
+    fn unchanged_pixels_are_not_dirty() {
+        let mut display = Display::<8, 8, 2, 4, TriColor>::new();
+        display.clear_dirty();
+
+        display.set_pixel(Pixel(Point::new(0, 0), TriColor::White));
+        display.clear(TriColor::White);
+        assert_eq!(display.dirty_area(), None);
+
+        display.set_pixel(Pixel(Point::new(3, 1), TriColor::Red));
+        assert_eq!(
+            display.dirty_area(),
+            Some(Rectangle::new(Point::new(0, 1), Size::new(8, 1)))
+        );
+    }
+
+    const ROTATIONS: [DisplayRotation; 4] = [
+        DisplayRotation::Rotate0,
+        DisplayRotation::Rotate90,
+        DisplayRotation::Rotate180,
+        DisplayRotation::Rotate270,
+    ];
+    const MIRRORS: [DisplayMirror; 4] = [
+        DisplayMirror::None,
+        DisplayMirror::Horizontal,
+        DisplayMirror::Vertical,
+        DisplayMirror::Both,
+    ];
+
+    type TestDisplay = Display<24, 21, 10, { buffer_len::<TriColor>(24, 10) }, TriColor>;
+
+    fn test_display(rotation: DisplayRotation, mirror: DisplayMirror) -> TestDisplay {
+        let mut display = TestDisplay::new();
+        display.set_rotation(rotation);
+        display.set_mirror(mirror);
+        let bounding_box = display.bounding_box();
+        display
+            .fill_contiguous(
+                &bounding_box,
+                (0..).map(|i| [TriColor::Red, TriColor::White, TriColor::Black][i % 3]),
+            )
+            .unwrap();
+        display
+    }
+
+    #[test]
+    fn fill_solid_matches_drawing_pixels() {
+        let areas = [
+            Rectangle::new(Point::new(3, 2), Size::new(13, 5)),
+            Rectangle::new(Point::new(-4, -2), Size::new(8, 30)),
+            Rectangle::new(Point::new(9, 1), Size::new(1, 1)),
+            Rectangle::new(Point::new(5, 5), Size::new(0, 3)),
+            Rectangle::new(Point::new(-5, -5), Size::new(50, 50)),
+        ];
+        for rotation in ROTATIONS {
+            for mirror in MIRRORS {
+                for area in &areas {
+                    for color in [TriColor::Black, TriColor::White, TriColor::Red] {
+                        let mut expected = test_display(rotation, mirror);
+                        expected
+                            .draw_iter(area.points().map(|point| Pixel(point, color)))
+                            .unwrap();
+                        let mut display = test_display(rotation, mirror);
+                        display.fill_solid(area, color).unwrap();
+
+                        assert_eq!(
+                            display.buffer, expected.buffer,
+                            "{rotation:?} {mirror:?} {area:?} {color:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fill_contiguous_matches_drawing_pixels() {
+        let area = Rectangle::new(Point::new(-3, 2), Size::new(17, 6));
+        let color = |i: usize| match i % 7 {
+            0..=2 => TriColor::Black,
+            3 => TriColor::Red,
+            _ => TriColor::White,
+        };
+        for rotation in ROTATIONS {
+            for mirror in MIRRORS {
+                let mut expected = TestDisplay::new();
+                expected.set_rotation(rotation);
+                expected.set_mirror(mirror);
+                expected
+                    .draw_iter(
+                        area.points()
+                            .enumerate()
+                            .map(|(i, point)| Pixel(point, color(i))),
+                    )
+                    .unwrap();
+                let mut display = TestDisplay::new();
+                display.set_rotation(rotation);
+                display.set_mirror(mirror);
+                display.fill_contiguous(&area, (0..).map(color)).unwrap();
+
+                assert_eq!(display.buffer, expected.buffer, "{rotation:?} {mirror:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn fill_contiguous_clipped_on_all_sides_matches_drawing_pixels() {
+        let area = Rectangle::new(Point::new(-2, -3), Size::new(30, 30));
+        let color = |i: usize| match i % 5 {
+            0 => TriColor::Red,
+            1 | 2 => TriColor::Black,
+            _ => TriColor::White,
+        };
+        for rotation in ROTATIONS {
+            for mirror in MIRRORS {
+                let mut expected = TestDisplay::new();
+                expected.set_rotation(rotation);
+                expected.set_mirror(mirror);
+                expected
+                    .draw_iter(
+                        area.points()
+                            .enumerate()
+                            .map(|(i, point)| Pixel(point, color(i))),
+                    )
+                    .unwrap();
+                let mut display = TestDisplay::new();
+                display.set_rotation(rotation);
+                display.set_mirror(mirror);
+                display.fill_contiguous(&area, (0..).map(color)).unwrap();
+
+                assert_eq!(display.buffer, expected.buffer, "{rotation:?} {mirror:?}");
+                assert_eq!(display.dirty, expected.dirty, "{rotation:?} {mirror:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn fill_contiguous_stops_when_colors_run_out() {
+        let area = Rectangle::new(Point::new(1, 1), Size::new(5, 3));
+        let mut expected = TestDisplay::new();
+        expected
+            .draw_iter(
+                area.points()
+                    .take(7)
+                    .map(|point| Pixel(point, TriColor::Black)),
+            )
+            .unwrap();
+        let mut display = TestDisplay::new();
+        display
+            .fill_contiguous(&area, core::iter::repeat_n(TriColor::Black, 7))
+            .unwrap();
+
+        assert_eq!(display.buffer, expected.buffer);
+    }
+
+    #[test]
+    fn fill_solid_marks_changed_lines_dirty() {
+        let mut display = Display::<24, 24, 8, 24, Color>::new();
+        display.clear_dirty();
+
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(0, 0), Size::new(24, 8)),
+                Color::White,
+            )
+            .unwrap();
+        assert_eq!(display.dirty_area(), None);
+
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(9, 2), Size::new(3, 4)),
+                Color::Black,
+            )
+            .unwrap();
+        assert_eq!(
+            display.dirty_area(),
+            Some(Rectangle::new(Point::new(8, 2), Size::new(8, 4)))
+        );
+    }
+
+    /// Draws a 1 bit per pixel image pixel by pixel.
+    fn draw_image<D>(display: &mut D, data: &[u8], area: Rectangle, on: D::Color, off: D::Color)
+    where
+        D: DrawTarget<Error = Infallible>,
+    {
+        let bytes_per_row = area.size.width.div_ceil(8) as usize;
+        let pixels = area.points().map(|point| {
+            let image = point - area.top_left;
+            let (x, y) = (image.x as usize, image.y as usize);
+            let bit = data[y * bytes_per_row + x / 8] & (0x80 >> (x % 8));
+            Pixel(point, if bit != 0 { on } else { off })
+        });
+        display.draw_iter(pixels).unwrap();
+    }
+
+    #[test]
+    fn blit_matches_drawing_pixels() {
+        let data: [u8; 36] = core::array::from_fn(|i| (i as u8).wrapping_mul(0x9D) ^ 0x5A);
+        let areas = [
+            Rectangle::new(Point::new(8, 2), Size::new(16, 6)),
+            Rectangle::new(Point::new(0, 0), Size::new(20, 8)),
+            Rectangle::new(Point::new(3, 1), Size::new(13, 8)),
+            Rectangle::new(Point::new(-5, -3), Size::new(24, 12)),
+            Rectangle::new(Point::new(14, 6), Size::new(11, 8)),
+        ];
+        for rotation in ROTATIONS {
+            for mirror in MIRRORS {
+                for area in areas {
+                    let mut expected = Display::<24, 21, 10, 30, Color>::new();
+                    expected.set_rotation(rotation);
+                    expected.set_mirror(mirror);
+                    draw_image(&mut expected, &data, area, Color::White, Color::Black);
+                    let mut display = Display::<24, 21, 10, 30, Color>::new();
+                    display.set_rotation(rotation);
+                    display.set_mirror(mirror);
+                    display
+                        .blit(
+                            &data,
+                            area.top_left.x,
+                            area.top_left.y,
+                            area.size.width,
+                            area.size.height,
+                        )
+                        .unwrap();
+
+                    assert_eq!(
+                        display.buffer, expected.buffer,
+                        "{rotation:?} {mirror:?} {area:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tri_color_blit_writes_both_planes() {
+        let bw = [0b1100_1100, 0b1010_0000];
+        let red = [0b1111_0000, 0b0000_0000];
+        let area = Rectangle::new(Point::new(4, 1), Size::new(4, 2));
+        let mut display = test_display(DisplayRotation::Rotate0, DisplayMirror::None);
+        display.blit(&bw, &red, 4, 1, 4, 2).unwrap();
+
+        for (point, expected) in area.points().zip([
+            TriColor::Red,
+            TriColor::Red,
+            TriColor::Red,
+            TriColor::Red,
+            TriColor::White,
+            TriColor::Black,
+            TriColor::White,
+            TriColor::Black,
+        ]) {
+            assert_eq!(display.get_pixel(point), Some(expected), "{point:?}");
+        }
+        assert_eq!(display.get_pixel(Point::new(3, 1)), Some(TriColor::Red));
+        assert_eq!(display.get_pixel(Point::new(8, 1)), Some(TriColor::Black));
+    }
+
+    #[test]
+    fn blit_marks_changed_lines_dirty() {
+        let mut display = Display::<24, 24, 8, 24, Color>::new();
+        display.clear_dirty();
+
+        display.blit(&[0xFF, 0xFF], 3, 1, 9, 1).unwrap();
+        assert_eq!(display.dirty_area(), None);
+
+        display.blit(&[0xFF, 0x00], 3, 1, 9, 1).unwrap();
+        assert_eq!(
+            display.dirty_area(),
+            Some(Rectangle::new(Point::new(0, 1), Size::new(16, 1)))
+        );
+    }
+
+    #[test]
+    fn blit_rejects_short_data() {
+        let mut display = Display::<24, 24, 8, 24, Color>::new();
+        display.clear_dirty();
+
+        assert!(matches!(
+            display.blit(&[0x00; 3], 0, 0, 9, 2),
+            Err(Error::InvalidBufferSize)
+        ));
+        assert_eq!(display.dirty_area(), None);
+
+        let mut display = test_display(DisplayRotation::Rotate0, DisplayMirror::None);
+        let before = display.buffer;
+        assert!(matches!(
+            display.blit(&[0x00; 2], &[0x00; 1], 0, 0, 8, 2),
+            Err(Error::InvalidBufferSize)
+        ));
+        assert_eq!(display.buffer, before);
+    }
+
+    #[test]
+    fn viewport_uses_local_coordinates_and_clips() {
+        let mut display = Display::<24, 24, 8, 24, Color>::new();
+        let mut viewport = display.viewport(Rectangle::new(Point::new(4, 2), Size::new(6, 3)));
+        assert_eq!(viewport.size(), Size::new(6, 3));
+
+        viewport
+            .draw_iter([
+                Pixel(Point::new(0, 0), Color::Black),
+                Pixel(Point::new(6, 0), Color::Black),
+                Pixel(Point::new(-1, 1), Color::Black),
+            ])
+            .unwrap();
+        viewport
+            .fill_solid(
+                &Rectangle::new(Point::new(4, 1), Size::new(10, 10)),
+                Color::Black,
+            )
+            .unwrap();
+
+        let black: alloc::vec::Vec<_> = display
+            .bounding_box()
+            .points()
+            .filter(|&point| display.get_pixel(point) == Some(Color::Black))
+            .collect();
+        assert_eq!(
+            black,
+            [
+                Point::new(4, 2),
+                Point::new(8, 3),
+                Point::new(9, 3),
+                Point::new(8, 4),
+                Point::new(9, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn viewport_fill_contiguous_is_clipped() {
+        let area = Rectangle::new(Point::new(-2, 1), Size::new(9, 3));
+        let color = |i: usize| [TriColor::Black, TriColor::Red, TriColor::White][i % 3];
+        let viewport_area = Rectangle::new(Point::new(5, 1), Size::new(5, 3));
+
+        let mut expected = test_display(DisplayRotation::Rotate90, DisplayMirror::Both);
+        expected
+            .draw_iter(area.points().enumerate().filter_map(|(i, point)| {
+                let point = point + viewport_area.top_left;
+                viewport_area
+                    .contains(point)
+                    .then_some(Pixel(point, color(i)))
+            }))
+            .unwrap();
+        let mut display = test_display(DisplayRotation::Rotate90, DisplayMirror::Both);
+        display
+            .viewport(viewport_area)
+            .fill_contiguous(&area, (0..).map(color))
+            .unwrap();
+
+        assert_eq!(display.buffer, expected.buffer);
+    }
+
+    #[test]
+    fn viewport_buffer_area_is_byte_aligned() {
+        let mut display = Display::<24, 24, 8, 24, Color>::new();
+        assert_eq!(
+            display
+                .viewport(Rectangle::new(Point::new(9, 2), Size::new(4, 3)))
+                .buffer_area(),
+            Some(Rectangle::new(Point::new(8, 2), Size::new(8, 3)))
+        );
+
+        display.set_rotation(DisplayRotation::Rotate90);
+        assert_eq!(
+            display
+                .viewport(Rectangle::new(Point::new(1, 2), Size::new(3, 4)))
+                .buffer_area(),
+            Some(Rectangle::new(Point::new(16, 1), Size::new(8, 3)))
+        );
+
+        assert_eq!(
+            display
+                .viewport(Rectangle::new(Point::new(30, 2), Size::new(3, 4)))
+                .buffer_area(),
+            None
+        );
+    }
+
+    #[test]
+    fn gray_levels_are_set_in_both_buffers() {
+        let mut display = Display::<8, 8, 1, 2, GrayLevel>::new();
+        assert_eq!(display.buffer, [0b1111_1111, 0b1111_1111]);
+
+        display.set_pixel(Pixel(Point::new(0, 0), GrayLevel::Black));
+        display.set_pixel(Pixel(Point::new(1, 0), GrayLevel::DarkGray));
+        display.set_pixel(Pixel(Point::new(2, 0), GrayLevel::LightGray));
+        display.set_pixel(Pixel(Point::new(3, 0), GrayLevel::DarkGray));
+        display.set_pixel(Pixel(Point::new(3, 0), GrayLevel::White));
+
+        assert_eq!(display.bw_buffer(), [0b0011_1111]);
+        assert_eq!(display.red_buffer(), [0b0101_1111]);
+        for (x, expected) in [
+            GrayLevel::Black,
+            GrayLevel::DarkGray,
+            GrayLevel::LightGray,
+            GrayLevel::White,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            assert_eq!(display.get_pixel(Point::new(x as i32, 0)), Some(expected));
+        }
+    }
+
+    #[test]
+    fn inverted_target_swaps_black_and_white() {
+        use embedded_graphics::{draw_target::DrawTargetExt, pixelcolor::BinaryColor};
+
+        let mut display = Display::<8, 8, 2, 4, TriColor>::new();
+        let mut inverted = Inverted::new(&mut display);
+        inverted.clear(TriColor::White).unwrap();
+        inverted
+            .fill_solid(
+                &Rectangle::new(Point::zero(), Size::new(2, 1)),
+                TriColor::Red,
+            )
+            .unwrap();
+        inverted
+            .color_converted()
+            .draw_iter([Pixel(Point::new(2, 0), BinaryColor::Off)])
+            .unwrap();
+
+        assert_eq!(display.get_pixel(Point::new(0, 0)), Some(TriColor::Red));
+        assert_eq!(display.get_pixel(Point::new(2, 0)), Some(TriColor::White));
+        assert_eq!(display.get_pixel(Point::new(3, 0)), Some(TriColor::Black));
+    }
 }
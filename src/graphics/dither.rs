@@ -0,0 +1,414 @@
+//! Dithering of full-color and grayscale images.
+//!
+//! [`Dithered`] wraps a draw target with one of the display colors and accepts any color that
+//! can be converted to [`Rgb888`], like [`Rgb565`](embedded_graphics::pixelcolor::Rgb565) images
+//! from `tinybmp` or [`Gray8`](embedded_graphics::pixelcolor::Gray8) gradients. Every pixel is
+//! mapped to the [`Palette`] of the target color, with dithering to approximate the colors in
+//! between.
+//!
+//! ```
+//! use embedded_graphics::{
+//!     pixelcolor::Rgb888,
+//!     prelude::*,
+//!     primitives::{PrimitiveStyle, Rectangle},
+//! };
+//! use weact_studio_epd::graphics::{
+//!     dither::{Dithered, Dithering},
+//!     Display290TriColor,
+//! };
+//!
+//! let mut display = Display290TriColor::new();
+//! let mut dithered =
+//!     Dithered::<_, Rgb888>::new(&mut display, Dithering::FloydSteinberg).unwrap();
+//! Rectangle::new(Point::new(10, 10), Size::new(50, 20))
+//!     .into_styled(PrimitiveStyle::with_fill(Rgb888::new(255, 128, 0)))
+//!     .draw(&mut dithered)
+//!     .unwrap();
+//! ```
+
+use core::marker::PhantomData;
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point},
+    pixelcolor::{PixelColor, Rgb888, RgbColor},
+    primitives::{PointsIter, Rectangle},
+    Pixel,
+};
+
+use crate::{
+    color::{Color, GrayLevel, TriColor},
+    Error,
+};
+
+/// Set of colors a display can show, used as the target of dithering.
+pub trait Palette: PixelColor + 'static {
+    /// The colors of the palette and the RGB values they are shown as.
+    const COLORS: &'static [(Self, Rgb888)];
+
+    /// Largest difference in a color channel between neighbouring palette colors.
+    ///
+    /// Used as the amplitude of the threshold pattern of ordered dithering.
+    const STEP: u8 = 255;
+
+    /// The palette color closest to `rgb`.
+    ///
+    /// Distances are weighted by how bright each channel is perceived.
+    fn nearest(rgb: [i32; 3]) -> (Self, Rgb888) {
+        let distance = |color: Rgb888| {
+            let [r, g, b] = [
+                rgb[0] - i32::from(color.r()),
+                rgb[1] - i32::from(color.g()),
+                rgb[2] - i32::from(color.b()),
+            ];
+            3 * r * r + 6 * g * g + b * b
+        };
+        *Self::COLORS
+            .iter()
+            .min_by_key(|(_, color)| distance(*color))
+            .expect("palette is empty")
+    }
+}
+
+/// Palette entry for a color, shown as its RGB value.
+macro_rules! entry {
+    ($color:expr) => {{
+        let [r, g, b] = $color.rgb();
+        ($color, Rgb888::new(r, g, b))
+    }};
+}
+
+impl Palette for Color {
+    const COLORS: &'static [(Self, Rgb888)] = &[entry!(Color::Black), entry!(Color::White)];
+}
+
+impl Palette for TriColor {
+    const COLORS: &'static [(Self, Rgb888)] = &[
+        entry!(TriColor::Black),
+        entry!(TriColor::White),
+        entry!(TriColor::Red),
+    ];
+}
+
+impl Palette for GrayLevel {
+    const COLORS: &'static [(Self, Rgb888)] = &[
+        entry!(GrayLevel::Black),
+        entry!(GrayLevel::DarkGray),
+        entry!(GrayLevel::LightGray),
+        entry!(GrayLevel::White),
+    ];
+    const STEP: u8 = 85;
+}
+
+/// Dithering algorithm.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dithering {
+    /// Ordered dithering with a 4x4 Bayer matrix.
+    ///
+    /// Every pixel is mapped on its own, so this works the same for all drawing operations and
+    /// gives a regular pattern.
+    #[default]
+    Ordered,
+    /// Floyd–Steinberg error diffusion.
+    ///
+    /// Gives a more natural result for photos, but needs the pixels in order. It's used for areas
+    /// filled with [`DrawTarget::fill_contiguous`], which is how images are drawn. Other drawing
+    /// operations fall back to ordered dithering.
+    ///
+    /// The error of the previous row is kept in the [`Dithered`] value, which makes it about
+    /// 2.4 KB large by default, see [`Dithered`] for how to reduce this.
+    FloydSteinberg,
+}
+
+/// 4x4 Bayer threshold matrix.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Draw target adapter that dithers colors of type `IC` to the palette of the wrapped target.
+///
+/// Floyd–Steinberg dithering keeps the error of one row of up to `MAX_WIDTH` pixels in an array
+/// of `6 * MAX_WIDTH` bytes inside the adapter, so with the default of 400 pixels, the width of
+/// the largest display, every `Dithered` value takes about 2.4 KB of the stack. Set `MAX_WIDTH`
+/// to the width of the target to use less, for example
+/// `Dithered::<_, Rgb565, 128>::new(&mut display, dithering)` for an unrotated 2.9 inch display.
+/// The target can't be wider than `MAX_WIDTH`. Pixels of images that extend to the right of the
+/// target only pass their error on to the right.
+pub struct Dithered<'a, D, IC, const MAX_WIDTH: usize = 400> {
+    target: &'a mut D,
+    dithering: Dithering,
+    errors: [[i16; 3]; MAX_WIDTH],
+    _color: PhantomData<IC>,
+}
+
+impl<'a, D, IC, const MAX_WIDTH: usize> Dithered<'a, D, IC, MAX_WIDTH>
+where
+    D: DrawTarget,
+    D::Color: Palette,
+    IC: PixelColor + Into<Rgb888>,
+{
+    /// Wrap `target` to draw colors of type `IC` to it using the given dithering algorithm.
+    ///
+    /// Returns [`Error::InvalidBufferSize`] if `target` is wider than `MAX_WIDTH`.
+    pub fn new(target: &'a mut D, dithering: Dithering) -> crate::Result<Self> {
+        if target.bounding_box().size.width as usize > MAX_WIDTH {
+            return Err(Error::InvalidBufferSize);
+        }
+        Ok(Self {
+            target,
+            dithering,
+            errors: [[0; 3]; MAX_WIDTH],
+            _color: PhantomData,
+        })
+    }
+
+    /// Get the dithering algorithm.
+    pub fn dithering(&self) -> Dithering {
+        self.dithering
+    }
+
+    /// Set the dithering algorithm.
+    pub fn set_dithering(&mut self, dithering: Dithering) {
+        self.dithering = dithering;
+    }
+
+    /// Get the wrapped draw target.
+    pub fn target(&self) -> &D {
+        self.target
+    }
+
+    /// Map a color at the given point to the palette using ordered dithering.
+    fn ordered(point: Point, color: IC) -> D::Color {
+        let threshold = i32::from(BAYER[point.y as usize % 4][point.x as usize % 4]);
+        let step = i32::from(D::Color::STEP);
+        // Offsets are centered around zero and spread over one palette step.
+        let offset = (2 * threshold + 1) * step / 32 - step / 2;
+        let rgb = rgb(color).map(|channel| channel + offset);
+        D::Color::nearest(rgb).0
+    }
+}
+
+impl<D, IC, const MAX_WIDTH: usize> Dimensions for Dithered<'_, D, IC, MAX_WIDTH>
+where
+    D: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D, IC, const MAX_WIDTH: usize> DrawTarget for Dithered<'_, D, IC, MAX_WIDTH>
+where
+    D: DrawTarget,
+    D::Color: Palette,
+    IC: PixelColor + Into<Rgb888>,
+{
+    type Color = IC;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point, Self::ordered(point, color))),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        if self.dithering == Dithering::Ordered {
+            let colors = area
+                .points()
+                .zip(colors)
+                .map(|(point, color)| Self::ordered(point, color));
+            return self.target.fill_contiguous(area, colors);
+        }
+
+        let width = area.size.width as usize;
+        if width == 0 {
+            return Ok(());
+        }
+        let errors = &mut self.errors;
+        errors.fill([0; 3]);
+        let mut right = [0; 3];
+        let mut below_left = [0; 3];
+        let mut below = [0; 3];
+        let colors = colors.into_iter().enumerate().map(|(index, color)| {
+            let x = index % width;
+            if x == 0 {
+                right = [0; 3];
+                below_left = [0; 3];
+                below = [0; 3];
+            }
+            let previous = errors.get(x).copied().unwrap_or_default();
+            let mut rgb = rgb(color);
+            for channel in 0..3 {
+                rgb[channel] =
+                    (rgb[channel] + i32::from(previous[channel]) + right[channel]).clamp(0, 255);
+            }
+            let (color, shown) = D::Color::nearest(rgb);
+            let error = [
+                rgb[0] - i32::from(shown.r()),
+                rgb[1] - i32::from(shown.g()),
+                rgb[2] - i32::from(shown.b()),
+            ];
+
+            // Spread the error over the neighbours to the right and in the next row.
+            right = error.map(|e| e * 7 / 16);
+            if let Some(slot) = x.checked_sub(1).and_then(|x| errors.get_mut(x)) {
+                *slot = [0, 1, 2].map(|c| (below_left[c] + error[c] * 3 / 16) as i16);
+            }
+            below_left = [0, 1, 2].map(|c| below[c] + error[c] * 5 / 16);
+            below = error.map(|e| e / 16);
+            if x == width - 1 {
+                if let Some(slot) = errors.get_mut(x) {
+                    *slot = below_left.map(|e| e as i16);
+                }
+            }
+            color
+        });
+        self.target.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let rgb: Rgb888 = color.into();
+        if let Some((color, _)) = D::Color::COLORS.iter().find(|(_, shown)| *shown == rgb) {
+            return self.target.fill_solid(area, *color);
+        }
+        let colors = area.points().map(|point| Self::ordered(point, color));
+        self.target.fill_contiguous(area, colors)
+    }
+}
+
+/// RGB channels of a color.
+fn rgb(color: impl Into<Rgb888>) -> [i32; 3] {
+    let color = color.into();
+    [color.r(), color.g(), color.b()].map(i32::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::{Display, DisplayBlackWhite};
+    use embedded_graphics::{
+        geometry::Size,
+        pixelcolor::{Gray8, Rgb565},
+    };
+
+    /// Number of pixels of the given color.
+    fn count<const BUFFER_SIZE: usize, C>(
+        display: &Display<16, 16, 16, BUFFER_SIZE, C>,
+        color: C,
+    ) -> usize
+    where
+        C: crate::color::ColorType + PixelColor,
+    {
+        display
+            .bounding_box()
+            .points()
+            .filter(|&point| display.get_pixel(point) == Some(color))
+            .count()
+    }
+
+    #[test]
+    fn exact_colors_are_not_dithered() {
+        let mut display = DisplayBlackWhite::<16, 16, 32>::new();
+        let mut dithered =
+            Dithered::<_, Rgb565>::new(&mut display, Dithering::FloydSteinberg).unwrap();
+        dithered
+            .fill_solid(
+                &Rectangle::new(Point::zero(), Size::new(8, 16)),
+                Rgb565::BLACK,
+            )
+            .unwrap();
+        dithered
+            .fill_contiguous(
+                &Rectangle::new(Point::new(8, 0), Size::new(8, 16)),
+                core::iter::repeat(Rgb565::WHITE),
+            )
+            .unwrap();
+
+        assert_eq!(display.buffer(), [0x00, 0xFF].repeat(16));
+    }
+
+    #[test]
+    fn target_wider_than_max_width_is_rejected() {
+        let mut display = DisplayBlackWhite::<16, 16, 32>::new();
+
+        let result = Dithered::<_, Rgb565, 8>::new(&mut display, Dithering::FloydSteinberg);
+
+        assert!(matches!(result, Err(Error::InvalidBufferSize)));
+        assert!(Dithered::<_, Rgb565, 16>::new(&mut display, Dithering::Ordered).is_ok());
+    }
+
+    #[test]
+    fn mid_gray_is_half_black() {
+        for dithering in [Dithering::Ordered, Dithering::FloydSteinberg] {
+            let mut display = Display::<16, 16, 16, 64, TriColor>::new();
+            let mut dithered = Dithered::<_, Gray8>::new(&mut display, dithering).unwrap();
+            let area = dithered.bounding_box();
+            dithered
+                .fill_contiguous(&area, core::iter::repeat(Gray8::new(128)))
+                .unwrap();
+
+            let black = count(&display, TriColor::Black);
+            assert!((112..=144).contains(&black), "{dithering:?}: {black}");
+            assert_eq!(count(&display, TriColor::Red), 0, "{dithering:?}");
+        }
+    }
+
+    #[test]
+    fn reddish_colors_use_red() {
+        let mut display = Display::<16, 16, 16, 64, TriColor>::new();
+        let mut dithered = Dithered::<_, Rgb888>::new(&mut display, Dithering::Ordered).unwrap();
+        let area = dithered.bounding_box();
+        dithered
+            .fill_solid(&area, Rgb888::new(255, 100, 100))
+            .unwrap();
+
+        let red = count(&display, TriColor::Red);
+        assert!(red > 128, "{red}");
+        assert_eq!(count(&display, TriColor::Black), 0);
+    }
+
+    #[test]
+    fn gray_levels_are_matched_exactly() {
+        let mut display = Display::<16, 16, 16, 64, GrayLevel>::new();
+        let mut dithered =
+            Dithered::<_, Gray8>::new(&mut display, Dithering::FloydSteinberg).unwrap();
+        let area = dithered.bounding_box();
+        dithered
+            .fill_contiguous(
+                &area,
+                (0..256).map(|i| Gray8::new(if i < 128 { 85 } else { 170 })),
+            )
+            .unwrap();
+
+        assert_eq!(count(&display, GrayLevel::DarkGray), 128);
+        assert_eq!(count(&display, GrayLevel::LightGray), 128);
+    }
+
+    #[test]
+    fn gradient_has_increasing_brightness() {
+        let mut display = DisplayBlackWhite::<16, 16, 32>::new();
+        let mut dithered =
+            Dithered::<_, Gray8>::new(&mut display, Dithering::FloydSteinberg).unwrap();
+        let area = dithered.bounding_box();
+        dithered
+            .fill_contiguous(&area, (0..256).map(|i| Gray8::new((i / 16 * 17) as u8)))
+            .unwrap();
+
+        let white_per_row: [u32; 16] = core::array::from_fn(|y| {
+            (0..16)
+                .filter(|&x| display.get_pixel(Point::new(x, y as i32)) == Some(Color::White))
+                .count() as u32
+        });
+        assert_eq!(white_per_row[0], 0);
+        assert_eq!(white_per_row[15], 16);
+        let total: u32 = white_per_row.iter().sum();
+        assert!((112..=144).contains(&total), "{white_per_row:?}");
+    }
+}
@@ -3,19 +3,37 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![warn(missing_docs)]
 
+#[cfg(any(test, feature = "mock"))]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 /// Color definitions
 mod color;
 mod command;
+mod controller;
 mod driver;
+mod error;
 mod flag;
 #[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
 #[cfg(feature = "graphics")]
 /// `embedded-graphics` support.
 pub mod graphics;
-mod lut;
+pub mod lut;
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod netpbm;
+mod panel;
+#[cfg_attr(docsrs, doc(cfg(feature = "simulator")))]
+#[cfg(feature = "simulator")]
+pub mod simulator;
 
-pub use color::{Color, TriColor};
+pub use color::{Color, GrayLevel, TriColor};
+pub use controller::*;
 pub use driver::*;
+pub use error::Error;
+pub use panel::*;
 
-/// Alias for `Result<T, DisplayError>`.
-pub type Result<T> = core::result::Result<T, display_interface::DisplayError>;
+/// Alias for `Result<T, Error>`.
+pub type Result<T> = core::result::Result<T, Error>;
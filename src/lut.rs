@@ -1,3 +1,397 @@
+//! Waveform lookup tables (LUTs).
+//!
+//! The waveform defines the voltages that are applied to the pixels during a refresh. The
+//! controllers load a waveform from their OTP memory, but a custom [`Lut`] can be used instead,
+//! for example to reduce ghosting on panels from a particular batch. See
+//! [`DisplayDriver::set_fast_refresh_lut`](crate::DisplayDriver::set_fast_refresh_lut) and
+//! [`DisplayDriver::set_full_refresh_lut`](crate::DisplayDriver::set_full_refresh_lut).
+//!
+//! ```
+//! use weact_studio_epd::lut::{Lut, Phase, VoltageSource};
+//!
+//! // Start from the built-in fast refresh waveform and drive white pixels a bit longer.
+//! let mut lut = Lut::SSD1680_FAST_REFRESH;
+//! let mut timing = lut.timing(0);
+//! timing.phase_lengths[0] += 2;
+//! lut.set_timing(0, timing);
+//! assert_eq!(lut.voltage(1, 0, Phase::A), VoltageSource::Vsl);
+//! assert!(lut.validate().is_ok());
+//! ```
+
+use crate::{Error, Result};
+
+/// Size of a waveform LUT in bytes.
+pub const LUT_LEN: usize = 153;
+
+/// Number of LUTs in a waveform: LUT0 to LUT3 select the source voltages for the four
+/// combinations of B/W and red RAM bits, LUT4 the VCOM voltage.
+pub const LUT_COUNT: usize = 5;
+
+/// Number of groups of phases in a waveform.
+pub const GROUP_COUNT: usize = 12;
+
+const VS_LEN: usize = LUT_COUNT * GROUP_COUNT;
+const TIMING_OFFSET: usize = VS_LEN;
+const TIMING_LEN: usize = 7;
+const FRAME_RATE_OFFSET: usize = TIMING_OFFSET + GROUP_COUNT * TIMING_LEN;
+const XON_OFFSET: usize = FRAME_RATE_OFFSET + GROUP_COUNT / 2;
+
+/// One of the four phases A to D of a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Phase A.
+    A,
+    /// Phase B.
+    B,
+    /// Phase C.
+    C,
+    /// Phase D.
+    D,
+}
+
+impl Phase {
+    /// Position of the phase's voltage source in a VS byte.
+    fn shift(self) -> u8 {
+        match self {
+            Phase::A => 6,
+            Phase::B => 4,
+            Phase::C => 2,
+            Phase::D => 0,
+        }
+    }
+}
+
+/// Voltage applied during a phase.
+///
+/// For the VCOM LUT (LUT4) the values select DCVCOM plus the given voltage and
+/// [`VoltageSource::Vsh2`] is not allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoltageSource {
+    /// VSS, i.e. no voltage.
+    Vss,
+    /// VSH1, drives pixels to black.
+    Vsh1,
+    /// VSL, drives pixels to white.
+    Vsl,
+    /// VSH2, drives pixels to red on tri-color displays.
+    Vsh2,
+}
+
+impl VoltageSource {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => VoltageSource::Vss,
+            0b01 => VoltageSource::Vsh1,
+            0b10 => VoltageSource::Vsl,
+            _ => VoltageSource::Vsh2,
+        }
+    }
+
+    fn bits(self) -> u8 {
+        match self {
+            VoltageSource::Vss => 0b00,
+            VoltageSource::Vsh1 => 0b01,
+            VoltageSource::Vsl => 0b10,
+            VoltageSource::Vsh2 => 0b11,
+        }
+    }
+}
+
+/// Timing of a group of phases.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupTiming {
+    /// Length of the phases A to D in frames (TP).
+    pub phase_lengths: [u8; 4],
+    /// Number of times phases A and B are repeated (SR).
+    pub repeat_ab: u8,
+    /// Number of times phases C and D are repeated (SR).
+    pub repeat_cd: u8,
+    /// Number of times the whole group is repeated (RP).
+    pub repeat: u8,
+}
+
+/// A 153 byte waveform LUT as used by the SSD1680 and the related controllers.
+///
+/// The LUT consists of:
+///
+/// * the voltage sources (VS) for each of the [`LUT_COUNT`] LUTs, [`GROUP_COUNT`] groups and
+///   four phases,
+/// * the [`GroupTiming`] (TP, SR and RP) of each group,
+/// * the frame rate (FR) of each group,
+/// * whether all gates are on (XON) during phases A/B and C/D of each group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lut {
+    bytes: [u8; LUT_LEN],
+}
+
+impl Lut {
+    /// The fast refresh waveform used for B/W displays with the SSD1680.
+    pub const SSD1680_FAST_REFRESH: Lut = Lut {
+        bytes: LUT_PARTIAL_UPDATE,
+    };
+
+    /// The fast refresh waveform used for the 1.54 inch display with the SSD1681.
+    ///
+    /// The driver sets matching driving voltages together with it.
+    pub const SSD1681_FAST_REFRESH: Lut = Lut {
+        bytes: LUT_PARTIAL_UPDATE_SSD1681,
+    };
+
+    /// The fast refresh waveform used for tri-color displays, which only drives pixels to black
+    /// or white.
+    pub const TRI_COLOR_FAST_REFRESH: Lut = Lut {
+        bytes: LUT_TRI_COLOR_FAST_UPDATE,
+    };
+
+    /// The 4-level grayscale waveform used for B/W displays with the SSD1680.
+    pub const SSD1680_GRAY2: Lut = Lut { bytes: LUT_GRAY2 };
+
+    /// Creates an empty waveform, which doesn't drive any pixel.
+    ///
+    /// Fill it using the setters. The frame rates are set to the value used by the built-in
+    /// waveforms.
+    pub const fn new() -> Self {
+        let mut bytes = [0; LUT_LEN];
+        let mut i = FRAME_RATE_OFFSET;
+        while i < XON_OFFSET {
+            bytes[i] = 0x22;
+            i += 1;
+        }
+        Self { bytes }
+    }
+
+    /// Creates a waveform from its raw bytes and [validates](Self::validate) it.
+    pub fn from_bytes(bytes: [u8; LUT_LEN]) -> Result<Self> {
+        let lut = Self { bytes };
+        lut.validate()?;
+        Ok(lut)
+    }
+
+    /// Creates a waveform from a slice of raw bytes and [validates](Self::validate) it.
+    ///
+    /// Returns [`Error::InvalidLut`] if the slice isn't [`LUT_LEN`] bytes long.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        Self::from_bytes(bytes.try_into().map_err(|_| Error::InvalidLut)?)
+    }
+
+    /// Get the raw bytes of the waveform, as sent to the controller.
+    pub fn as_bytes(&self) -> &[u8; LUT_LEN] {
+        &self.bytes
+    }
+
+    /// Checks that the waveform can be used.
+    ///
+    /// Returns [`Error::InvalidLut`] if the VCOM LUT uses [`VoltageSource::Vsh2`], which isn't a
+    /// valid VCOM setting, or if all phases have a length of zero so the waveform does nothing.
+    pub fn validate(&self) -> Result<()> {
+        let vcom_valid = (0..GROUP_COUNT).all(|group| {
+            [Phase::A, Phase::B, Phase::C, Phase::D]
+                .into_iter()
+                .all(|phase| self.voltage(LUT_COUNT - 1, group, phase) != VoltageSource::Vsh2)
+        });
+        let has_phases = (0..GROUP_COUNT).any(|group| {
+            self.timing(group)
+                .phase_lengths
+                .iter()
+                .any(|&length| length > 0)
+        });
+        if vcom_valid && has_phases {
+            Ok(())
+        } else {
+            Err(Error::InvalidLut)
+        }
+    }
+
+    /// Get the voltage source of a phase.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lut` is not less than [`LUT_COUNT`] or `group` not less than [`GROUP_COUNT`].
+    pub fn voltage(&self, lut: usize, group: usize, phase: Phase) -> VoltageSource {
+        VoltageSource::from_bits(self.bytes[Self::vs_index(lut, group)] >> phase.shift())
+    }
+
+    /// Set the voltage source of a phase.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lut` is not less than [`LUT_COUNT`] or `group` not less than [`GROUP_COUNT`].
+    pub fn set_voltage(&mut self, lut: usize, group: usize, phase: Phase, source: VoltageSource) {
+        let byte = &mut self.bytes[Self::vs_index(lut, group)];
+        *byte = (*byte & !(0b11 << phase.shift())) | source.bits() << phase.shift();
+    }
+
+    /// Get the timing of a group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `group` is not less than [`GROUP_COUNT`].
+    pub fn timing(&self, group: usize) -> GroupTiming {
+        let [tp_a, tp_b, sr_ab, tp_c, tp_d, sr_cd, rp] = self.timing_bytes(group);
+        GroupTiming {
+            phase_lengths: [tp_a, tp_b, tp_c, tp_d],
+            repeat_ab: sr_ab,
+            repeat_cd: sr_cd,
+            repeat: rp,
+        }
+    }
+
+    /// Set the timing of a group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `group` is not less than [`GROUP_COUNT`].
+    pub fn set_timing(&mut self, group: usize, timing: GroupTiming) {
+        let [tp_a, tp_b, tp_c, tp_d] = timing.phase_lengths;
+        let start = Self::timing_index(group);
+        self.bytes[start..start + TIMING_LEN].copy_from_slice(&[
+            tp_a,
+            tp_b,
+            timing.repeat_ab,
+            tp_c,
+            tp_d,
+            timing.repeat_cd,
+            timing.repeat,
+        ]);
+    }
+
+    /// Get the frame rate setting of a group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `group` is not less than [`GROUP_COUNT`].
+    pub fn frame_rate(&self, group: usize) -> u8 {
+        let (index, shift) = Self::frame_rate_position(group);
+        self.bytes[index] >> shift & 0x0F
+    }
+
+    /// Set the frame rate setting of a group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `group` is not less than [`GROUP_COUNT`] or `rate` doesn't fit in 4 bits.
+    pub fn set_frame_rate(&mut self, group: usize, rate: u8) {
+        assert!(rate <= 0x0F, "frame rate must fit in 4 bits");
+        let (index, shift) = Self::frame_rate_position(group);
+        self.bytes[index] = (self.bytes[index] & !(0x0F << shift)) | rate << shift;
+    }
+
+    /// Get whether all gates are on during phases A/B and C/D of a group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `group` is not less than [`GROUP_COUNT`].
+    pub fn gates_on(&self, group: usize) -> (bool, bool) {
+        let (ab, cd) = Self::gates_on_bits(group);
+        (self.xon_bit(ab), self.xon_bit(cd))
+    }
+
+    /// Set whether all gates are on during phases A/B and C/D of a group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `group` is not less than [`GROUP_COUNT`].
+    pub fn set_gates_on(&mut self, group: usize, ab: bool, cd: bool) {
+        let (ab_bit, cd_bit) = Self::gates_on_bits(group);
+        self.set_xon_bit(ab_bit, ab);
+        self.set_xon_bit(cd_bit, cd);
+    }
+
+    fn vs_index(lut: usize, group: usize) -> usize {
+        assert!(
+            lut < LUT_COUNT && group < GROUP_COUNT,
+            "invalid LUT or group"
+        );
+        lut * GROUP_COUNT + group
+    }
+
+    fn timing_index(group: usize) -> usize {
+        assert!(group < GROUP_COUNT, "invalid group");
+        TIMING_OFFSET + group * TIMING_LEN
+    }
+
+    fn timing_bytes(&self, group: usize) -> [u8; TIMING_LEN] {
+        let start = Self::timing_index(group);
+        let mut bytes = [0; TIMING_LEN];
+        bytes.copy_from_slice(&self.bytes[start..start + TIMING_LEN]);
+        bytes
+    }
+
+    /// Byte index and shift of the frame rate of a group. Even groups use the high nibble.
+    fn frame_rate_position(group: usize) -> (usize, u8) {
+        assert!(group < GROUP_COUNT, "invalid group");
+        let shift = if group.is_multiple_of(2) { 4 } else { 0 };
+        (FRAME_RATE_OFFSET + group / 2, shift)
+    }
+
+    /// Bit numbers of the XON bits of a group, counted from the most significant bit of the
+    /// first XON byte.
+    fn gates_on_bits(group: usize) -> (usize, usize) {
+        assert!(group < GROUP_COUNT, "invalid group");
+        (2 * group, 2 * group + 1)
+    }
+
+    fn xon_bit(&self, bit: usize) -> bool {
+        self.bytes[XON_OFFSET + bit / 8] & (0x80 >> (bit % 8)) != 0
+    }
+
+    fn set_xon_bit(&mut self, bit: usize, value: bool) {
+        let byte = &mut self.bytes[XON_OFFSET + bit / 8];
+        if value {
+            *byte |= 0x80 >> (bit % 8);
+        } else {
+            *byte &= !(0x80 >> (bit % 8));
+        }
+    }
+}
+
+impl Default for Lut {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A waveform for a range of temperatures.
+///
+/// Bands are used in ascending order of [`Self::max_temperature`]: a band applies to temperatures
+/// above the previous band's maximum up to and including its own. The last band also applies to
+/// higher temperatures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemperatureBand {
+    /// Highest temperature in degrees Celsius the waveform is used for.
+    pub max_temperature: i8,
+    /// The waveform.
+    pub lut: Lut,
+}
+
+impl TemperatureBand {
+    /// Find the waveform for a temperature in degrees Celsius.
+    ///
+    /// Returns `None` if `bands` is empty.
+    pub fn select(bands: &[TemperatureBand], temperature: i8) -> Option<&Lut> {
+        bands
+            .iter()
+            .find(|band| temperature <= band.max_temperature)
+            .or(bands.last())
+            .map(|band| &band.lut)
+    }
+
+    /// Checks that the bands are usable: non-empty, in ascending order of temperature and with
+    /// [valid](Lut::validate) waveforms.
+    ///
+    /// Returns [`Error::InvalidLut`] otherwise.
+    pub fn validate(bands: &[TemperatureBand]) -> Result<()> {
+        if bands.is_empty()
+            || bands
+                .windows(2)
+                .any(|pair| pair[0].max_temperature >= pair[1].max_temperature)
+        {
+            return Err(Error::InvalidLut);
+        }
+        bands.iter().try_for_each(|band| band.lut.validate())
+    }
+}
+
 #[rustfmt::skip]
 pub(crate) const LUT_PARTIAL_UPDATE: [u8; 153] = [
     0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT0]
@@ -20,3 +414,190 @@ pub(crate) const LUT_PARTIAL_UPDATE: [u8; 153] = [
     0x22, 0x22, 0x22, 0x22, 0x22, 0x22, // FR[n]
     0x00, 0x00, 0x00, // XON[nXY]
 ];
+
+/// Fast refresh LUT for the SSD1681. Must be followed by the matching voltage settings.
+#[rustfmt::skip]
+pub(crate) const LUT_PARTIAL_UPDATE_SSD1681: [u8; 153] = [
+    0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT0]
+    0x80, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT1]
+    0x40, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT2]
+    0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT3]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT4]
+    0x0F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[0A], TP[0B], SR[0AB], TP[0C], TP[0D], SR[0CD], RP[0]
+    0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[1A], TP[1B], SR[1AB], TP[1C], TP[1D], SR[1CD], RP[1]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[2A], TP[2B], SR[2AB], TP[2C], TP[2D], SR[2CD], RP[2]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[3A], TP[3B], SR[3AB], TP[3C], TP[3D], SR[3CD], RP[3]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[4A], TP[4B], SR[4AB], TP[4C], TP[4D], SR[4CD], RP[4]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[5A], TP[5B], SR[5AB], TP[5C], TP[5D], SR[5CD], RP[5]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[6A], TP[6B], SR[6AB], TP[6C], TP[6D], SR[6CD], RP[6]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[7A], TP[7B], SR[7AB], TP[7C], TP[7D], SR[7CD], RP[7]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[8A], TP[8B], SR[8AB], TP[8C], TP[8D], SR[8CD], RP[8]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[9A], TP[9B], SR[9AB], TP[9C], TP[9D], SR[9CD], RP[9]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[10A], TP[10B], SR[10AB], TP[10C], TP[10D], SR[10CD], RP[10]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[11A], TP[11B], SR[11AB], TP[11C], TP[11D], SR[11CD], RP[11]
+    0x22, 0x22, 0x22, 0x22, 0x22, 0x22, // FR[n]
+    0x00, 0x00, 0x00, // XON[nXY]
+];
+
+/// Fast refresh LUT for tri-color displays. Only drives pixels to black or white and is meant to
+/// be used with the red RAM bypassed.
+#[rustfmt::skip]
+pub(crate) const LUT_TRI_COLOR_FAST_UPDATE: [u8; 153] = [
+    0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT0]
+    0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT1]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT2]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT3]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT4]
+    0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, // TP[0A], TP[0B], SR[0AB], TP[0C], TP[0D], SR[0CD], RP[0]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[1A], TP[1B], SR[1AB], TP[1C], TP[1D], SR[1CD], RP[1]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[2A], TP[2B], SR[2AB], TP[2C], TP[2D], SR[2CD], RP[2]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[3A], TP[3B], SR[3AB], TP[3C], TP[3D], SR[3CD], RP[3]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[4A], TP[4B], SR[4AB], TP[4C], TP[4D], SR[4CD], RP[4]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[5A], TP[5B], SR[5AB], TP[5C], TP[5D], SR[5CD], RP[5]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[6A], TP[6B], SR[6AB], TP[6C], TP[6D], SR[6CD], RP[6]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[7A], TP[7B], SR[7AB], TP[7C], TP[7D], SR[7CD], RP[7]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[8A], TP[8B], SR[8AB], TP[8C], TP[8D], SR[8CD], RP[8]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[9A], TP[9B], SR[9AB], TP[9C], TP[9D], SR[9CD], RP[9]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[10A], TP[10B], SR[10AB], TP[10C], TP[10D], SR[10CD], RP[10]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[11A], TP[11B], SR[11AB], TP[11C], TP[11D], SR[11CD], RP[11]
+    0x22, 0x22, 0x22, 0x22, 0x22, 0x22, // FR[n]
+    0x00, 0x00, 0x00, // XON[nXY]
+];
+
+/// 4-level grayscale LUT for the SSD1680. Must be followed by the matching voltage settings.
+///
+/// The gray level of a pixel selects the waveform: LUT0 is black (B/W and red RAM bits `0`),
+/// LUT1 light gray (B/W bit `1`), LUT2 dark gray (red bit `1`) and LUT3 white (both bits `1`).
+#[rustfmt::skip]
+pub(crate) const LUT_GRAY2: [u8; 153] = [
+    0x00, 0x60, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT0]
+    0x28, 0x60, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT1]
+    0x20, 0x60, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT2]
+    0x2A, 0x60, 0x15, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT3]
+    0x00, 0x90, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // VS[nX-LUT4]
+    0x00, 0x02, 0x00, 0x05, 0x14, 0x00, 0x00, // TP[0A], TP[0B], SR[0AB], TP[0C], TP[0D], SR[0CD], RP[0]
+    0x1E, 0x1E, 0x00, 0x00, 0x00, 0x00, 0x01, // TP[1A], TP[1B], SR[1AB], TP[1C], TP[1D], SR[1CD], RP[1]
+    0x00, 0x02, 0x00, 0x05, 0x14, 0x00, 0x00, // TP[2A], TP[2B], SR[2AB], TP[2C], TP[2D], SR[2CD], RP[2]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[3A], TP[3B], SR[3AB], TP[3C], TP[3D], SR[3CD], RP[3]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[4A], TP[4B], SR[4AB], TP[4C], TP[4D], SR[4CD], RP[4]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[5A], TP[5B], SR[5AB], TP[5C], TP[5D], SR[5CD], RP[5]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[6A], TP[6B], SR[6AB], TP[6C], TP[6D], SR[6CD], RP[6]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[7A], TP[7B], SR[7AB], TP[7C], TP[7D], SR[7CD], RP[7]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[8A], TP[8B], SR[8AB], TP[8C], TP[8D], SR[8CD], RP[8]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[9A], TP[9B], SR[9AB], TP[9C], TP[9D], SR[9CD], RP[9]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[10A], TP[10B], SR[10AB], TP[10C], TP[10D], SR[10CD], RP[10]
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // TP[11A], TP[11B], SR[11AB], TP[11C], TP[11D], SR[11CD], RP[11]
+    0x24, 0x22, 0x22, 0x22, 0x23, 0x32, // FR[n]
+    0x00, 0x00, 0x00, // XON[nXY]
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_luts_are_valid() {
+        for lut in [
+            Lut::SSD1680_FAST_REFRESH,
+            Lut::SSD1681_FAST_REFRESH,
+            Lut::TRI_COLOR_FAST_REFRESH,
+            Lut::SSD1680_GRAY2,
+        ] {
+            assert!(lut.validate().is_ok(), "{lut:?}");
+        }
+    }
+
+    #[test]
+    fn fields_match_the_byte_layout() {
+        let lut = Lut::SSD1680_FAST_REFRESH;
+        assert_eq!(lut.voltage(0, 1, Phase::A), VoltageSource::Vsh1);
+        assert_eq!(lut.voltage(1, 0, Phase::A), VoltageSource::Vsl);
+        assert_eq!(lut.voltage(4, 0, Phase::A), VoltageSource::Vss);
+        assert_eq!(
+            lut.timing(0),
+            GroupTiming {
+                phase_lengths: [0x0A, 0x00, 0x00, 0x00],
+                repeat_ab: 0,
+                repeat_cd: 0,
+                repeat: 2,
+            }
+        );
+        assert_eq!(lut.timing(2).phase_lengths, [0x01, 0x00, 0x00, 0x00]);
+        assert_eq!(lut.frame_rate(0), 2);
+        assert_eq!(Lut::SSD1680_GRAY2.frame_rate(0), 2);
+        assert_eq!(Lut::SSD1680_GRAY2.frame_rate(1), 4);
+    }
+
+    #[test]
+    fn setters_write_the_byte_layout() {
+        let mut lut = Lut::new();
+        lut.set_voltage(3, 11, Phase::C, VoltageSource::Vsh2);
+        lut.set_timing(
+            11,
+            GroupTiming {
+                phase_lengths: [1, 2, 3, 4],
+                repeat_ab: 5,
+                repeat_cd: 6,
+                repeat: 7,
+            },
+        );
+        lut.set_frame_rate(3, 0x5);
+        lut.set_gates_on(4, false, true);
+
+        let bytes = lut.as_bytes();
+        assert_eq!(bytes[3 * 12 + 11], 0b0000_1100);
+        assert_eq!(bytes[60 + 11 * 7..60 + 12 * 7], [1, 2, 5, 3, 4, 6, 7]);
+        assert_eq!(bytes[144..150], [0x22, 0x25, 0x22, 0x22, 0x22, 0x22]);
+        assert_eq!(bytes[150..153], [0x00, 0b0100_0000, 0x00]);
+        assert_eq!(lut.gates_on(4), (false, true));
+    }
+
+    #[test]
+    fn temperature_band_selection() {
+        let bands = [
+            TemperatureBand {
+                max_temperature: 0,
+                lut: Lut::SSD1681_FAST_REFRESH,
+            },
+            TemperatureBand {
+                max_temperature: 25,
+                lut: Lut::SSD1680_FAST_REFRESH,
+            },
+        ];
+        assert!(TemperatureBand::validate(&bands).is_ok());
+        assert_eq!(
+            TemperatureBand::select(&bands, -20),
+            Some(&Lut::SSD1681_FAST_REFRESH)
+        );
+        assert_eq!(
+            TemperatureBand::select(&bands, 1),
+            Some(&Lut::SSD1680_FAST_REFRESH)
+        );
+        assert_eq!(
+            TemperatureBand::select(&bands, 60),
+            Some(&Lut::SSD1680_FAST_REFRESH)
+        );
+        assert_eq!(TemperatureBand::select(&[], 20), None);
+
+        assert!(TemperatureBand::validate(&[]).is_err());
+        assert!(TemperatureBand::validate(&[bands[1], bands[0]]).is_err());
+    }
+
+    #[test]
+    fn invalid_luts_are_rejected() {
+        assert!(matches!(Lut::new().validate(), Err(Error::InvalidLut)));
+
+        let mut lut = Lut::SSD1680_FAST_REFRESH;
+        lut.set_voltage(4, 0, Phase::A, VoltageSource::Vsh2);
+        assert!(matches!(
+            Lut::from_bytes(*lut.as_bytes()),
+            Err(Error::InvalidLut)
+        ));
+
+        assert!(matches!(Lut::from_slice(&[0; 152]), Err(Error::InvalidLut)));
+        assert_eq!(
+            Lut::from_slice(&LUT_PARTIAL_UPDATE).unwrap(),
+            Lut::SSD1680_FAST_REFRESH
+        );
+    }
+}
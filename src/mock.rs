@@ -0,0 +1,833 @@
+//! Mock hardware for testing code that uses the display driver on a host.
+//!
+//! The mocks implement both the blocking and the async traits so they can be used with and
+//! without the `blocking` feature. All mocks can be cloned; clones share their state, so keep a
+//! clone around to inspect or script a mock after moving it into the driver.
+//!
+//! ```
+//! # #[cfg(not(feature = "blocking"))]
+//! # {
+//! use weact_studio_epd::mock::{
+//!     block_on, MockBusyPin, MockDelay, MockInterface, MockResetPin, Transaction,
+//! };
+//! use weact_studio_epd::WeActStudio290BlackWhiteDriver;
+//!
+//! let interface = MockInterface::new();
+//! let mut driver = WeActStudio290BlackWhiteDriver::new(
+//!     interface.clone(),
+//!     MockBusyPin::new(),
+//!     MockResetPin::new(),
+//!     MockDelay::new(),
+//! );
+//!
+//! block_on(driver.sleep()).unwrap();
+//! assert_eq!(
+//!     interface.take_transactions(),
+//!     [Transaction::Command(0x10), Transaction::Data(vec![0x01])]
+//! );
+//! # }
+//! ```
+
+use alloc::{rc::Rc, vec, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    convert::Infallible,
+    future::Future,
+    marker::PhantomData,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use display_interface::{DataFormat, DisplayError};
+
+use crate::{command, netpbm::PixelSource, Color, Controller, Ssd1680, TriColor};
+
+/// A command or data write recorded by [`MockInterface`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transaction {
+    /// A command byte.
+    Command(u8),
+    /// Data bytes. Consecutive data writes are combined.
+    Data(Vec<u8>),
+}
+
+/// Display interface that records all commands and data sent to it.
+#[derive(Debug, Clone, Default)]
+pub struct MockInterface {
+    transactions: Rc<RefCell<Vec<Transaction>>>,
+}
+
+impl MockInterface {
+    /// Create a new interface without any recorded transactions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the recorded transactions.
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.transactions.borrow().clone()
+    }
+
+    /// Get the recorded transactions and clear them.
+    pub fn take_transactions(&self) -> Vec<Transaction> {
+        self.transactions.take()
+    }
+
+    fn record_commands(&mut self, commands: DataFormat<'_>) -> Result<(), DisplayError> {
+        let commands = to_bytes(commands)?;
+        self.transactions
+            .borrow_mut()
+            .extend(commands.into_iter().map(Transaction::Command));
+        Ok(())
+    }
+
+    fn record_data(&mut self, data: DataFormat<'_>) -> Result<(), DisplayError> {
+        let data = to_bytes(data)?;
+        let mut transactions = self.transactions.borrow_mut();
+        match transactions.last_mut() {
+            Some(Transaction::Data(previous)) => previous.extend(data),
+            _ => transactions.push(Transaction::Data(data)),
+        }
+        Ok(())
+    }
+}
+
+fn to_bytes(data: DataFormat<'_>) -> Result<Vec<u8>, DisplayError> {
+    let bytes = match data {
+        DataFormat::U8(bytes) => bytes.to_vec(),
+        DataFormat::U8Iter(bytes) => bytes.collect(),
+        DataFormat::U16(words) => words.iter().flat_map(|word| word.to_ne_bytes()).collect(),
+        DataFormat::U16BE(words) => words.iter().flat_map(|word| word.to_be_bytes()).collect(),
+        DataFormat::U16LE(words) => words.iter().flat_map(|word| word.to_le_bytes()).collect(),
+        DataFormat::U16BEIter(words) => words.flat_map(u16::to_be_bytes).collect(),
+        DataFormat::U16LEIter(words) => words.flat_map(u16::to_le_bytes).collect(),
+        _ => return Err(DisplayError::DataFormatNotImplemented),
+    };
+    Ok(bytes)
+}
+
+impl display_interface::WriteOnlyDataCommand for MockInterface {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record_commands(cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record_data(buf)
+    }
+}
+
+impl display_interface::AsyncWriteOnlyDataCommand for MockInterface {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record_commands(cmd)
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record_data(buf)
+    }
+}
+
+/// Display interface that emulates the controller by interpreting the commands sent to it.
+///
+/// The emulator keeps the B/W and red RAM planes and the image that is shown on the display
+/// after the last refresh. It supports the RAM window and counter commands, the data entry
+/// mode, the RAM options of "Display Update Control 1" (`0x21`) and refreshes with
+/// `MASTER_ACTIVATE`. Waveforms written with "Write LUT Register" (`0x32`) are kept but not
+/// emulated. Other commands are accepted but ignored.
+///
+/// The registers are reset by "SW Reset" (`0x12`) and when entering deep sleep, which can only
+/// be left with a hardware reset. The RAM is kept.
+///
+/// Coordinates are RAM coordinates: `x` counts sources and `y` counts gates, the same as in
+/// the frame buffers passed to the driver.
+///
+/// ```
+/// # #[cfg(not(feature = "blocking"))]
+/// # {
+/// use weact_studio_epd::mock::{block_on, Emulator, MockBusyPin, MockDelay, MockResetPin};
+/// use weact_studio_epd::{Color, WeActStudio290BlackWhiteDriver};
+///
+/// let emulator: Emulator = Emulator::new();
+/// let mut driver = WeActStudio290BlackWhiteDriver::new(
+///     emulator.clone(),
+///     MockBusyPin::new(),
+///     MockResetPin::new(),
+///     MockDelay::new(),
+/// );
+///
+/// let mut buffer = [0xFF; 128 / 8 * 296];
+/// buffer[0] = 0x7F;
+/// block_on(driver.init()).unwrap();
+/// block_on(driver.full_update_from_buffer(&buffer)).unwrap();
+///
+/// assert_eq!(emulator.displayed_color(0, 0), Color::Black);
+/// assert_eq!(emulator.displayed_color(1, 0), Color::White);
+/// # }
+/// ```
+pub struct Emulator<CTRL = Ssd1680> {
+    state: Rc<RefCell<EmulatorState>>,
+    _controller: PhantomData<CTRL>,
+}
+
+impl<CTRL> Clone for Emulator<CTRL> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            _controller: PhantomData,
+        }
+    }
+}
+
+impl<CTRL: Controller> Default for Emulator<CTRL> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<CTRL: Controller> Emulator<CTRL> {
+    /// Create a new emulator with white RAM and a white display.
+    pub fn new() -> Self {
+        let bytes_per_line = CTRL::MAX_WIDTH.div_ceil(8) as usize;
+        let height = CTRL::MAX_HEIGHT as usize;
+        let ram_size = bytes_per_line * height;
+        let mut state = EmulatorState {
+            bytes_per_line,
+            height,
+            bw_ram: vec![0xFF; ram_size],
+            red_ram: vec![0x00; ram_size],
+            displayed_bw: vec![0xFF; ram_size],
+            displayed_red: vec![0x00; ram_size],
+            command: None,
+            parameters: Vec::new(),
+            data_entry_mode: 0,
+            x_window: (0, 0),
+            y_window: (0, 0),
+            x_counter: 0,
+            y_counter: 0,
+            ram_options: 0,
+            update_control: 0,
+            lut: None,
+            refreshes: Vec::new(),
+        };
+        state.reset();
+        Self {
+            state: Rc::new(RefCell::new(state)),
+            _controller: PhantomData,
+        }
+    }
+
+    /// Contents of the B/W RAM, one bit per pixel and one byte for every 8 sources.
+    pub fn bw_ram(&self) -> Vec<u8> {
+        self.state.borrow().bw_ram.clone()
+    }
+
+    /// Contents of the red RAM, one bit per pixel and one byte for every 8 sources.
+    pub fn red_ram(&self) -> Vec<u8> {
+        self.state.borrow().red_ram.clone()
+    }
+
+    /// Color of a pixel on a B/W display after the last refresh.
+    ///
+    /// Panics if the pixel is outside of the controller RAM.
+    pub fn displayed_color(&self, x: u32, y: u32) -> Color {
+        let state = self.state.borrow();
+        if state.pixel(&state.displayed_bw, x, y) {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    /// Color of a pixel on a tri-color display after the last refresh.
+    ///
+    /// Panics if the pixel is outside of the controller RAM.
+    pub fn displayed_tri_color(&self, x: u32, y: u32) -> TriColor {
+        let state = self.state.borrow();
+        if state.pixel(&state.displayed_red, x, y) {
+            TriColor::Red
+        } else if state.pixel(&state.displayed_bw, x, y) {
+            TriColor::White
+        } else {
+            TriColor::Black
+        }
+    }
+
+    /// Copy of the top left `width` by `height` pixels shown on a B/W display after the last
+    /// refresh, for example to save it with [`netpbm`](crate::netpbm).
+    pub fn displayed_image(&self, width: u32, height: u32) -> DisplayedImage {
+        self.image(width, height, false)
+    }
+
+    /// Copy of the top left `width` by `height` pixels shown on a tri-color display after the
+    /// last refresh, for example to save it with [`netpbm`](crate::netpbm).
+    pub fn displayed_tri_color_image(&self, width: u32, height: u32) -> DisplayedImage {
+        self.image(width, height, true)
+    }
+
+    fn image(&self, width: u32, height: u32, tri_color: bool) -> DisplayedImage {
+        assert!(
+            width <= CTRL::MAX_WIDTH && height <= CTRL::MAX_HEIGHT,
+            "image is larger than the controller RAM"
+        );
+        let state = self.state.borrow();
+        DisplayedImage {
+            bw: state.displayed_bw.clone(),
+            red: tri_color.then(|| state.displayed_red.clone()),
+            bytes_per_line: state.bytes_per_line,
+            width,
+            height,
+        }
+    }
+
+    /// Waveform written with "Write LUT Register" (`0x32`) since the last reset, if any.
+    pub fn lut(&self) -> Option<Vec<u8>> {
+        self.state.borrow().lut.clone()
+    }
+
+    /// "Display Update Control 2" (`0x22`) values used for the refreshes done so far.
+    pub fn refreshes(&self) -> Vec<u8> {
+        self.state.borrow().refreshes.clone()
+    }
+
+    fn send_commands(&mut self, commands: DataFormat<'_>) -> Result<(), DisplayError> {
+        let mut state = self.state.borrow_mut();
+        for command in to_bytes(commands)? {
+            state.command(command);
+        }
+        Ok(())
+    }
+
+    fn send_data(&mut self, data: DataFormat<'_>) -> Result<(), DisplayError> {
+        let data = to_bytes(data)?;
+        self.state.borrow_mut().data::<CTRL>(&data);
+        Ok(())
+    }
+}
+
+impl<CTRL: Controller> display_interface::WriteOnlyDataCommand for Emulator<CTRL> {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        Emulator::send_commands(self, cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        Emulator::send_data(self, buf)
+    }
+}
+
+impl<CTRL: Controller> display_interface::AsyncWriteOnlyDataCommand for Emulator<CTRL> {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        Emulator::send_commands(self, cmd)
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        Emulator::send_data(self, buf)
+    }
+}
+
+/// Image shown by an [`Emulator`].
+#[derive(Debug, Clone)]
+pub struct DisplayedImage {
+    bw: Vec<u8>,
+    red: Option<Vec<u8>>,
+    bytes_per_line: usize,
+    width: u32,
+    height: u32,
+}
+
+impl PixelSource for DisplayedImage {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        let index = y as usize * self.bytes_per_line + (x / 8) as usize;
+        let bit = 0x80 >> (x % 8);
+        if self.red.as_ref().is_some_and(|red| red[index] & bit != 0) {
+            TriColor::Red.rgb()
+        } else if self.bw[index] & bit != 0 {
+            Color::White.rgb()
+        } else {
+            Color::Black.rgb()
+        }
+    }
+}
+
+struct EmulatorState {
+    bytes_per_line: usize,
+    height: usize,
+    bw_ram: Vec<u8>,
+    red_ram: Vec<u8>,
+    displayed_bw: Vec<u8>,
+    displayed_red: Vec<u8>,
+    command: Option<u8>,
+    parameters: Vec<u8>,
+    data_entry_mode: u8,
+    // Start and end addresses, in the direction the counter moves.
+    x_window: (u32, u32),
+    y_window: (u32, u32),
+    x_counter: u32,
+    y_counter: u32,
+    ram_options: u8,
+    update_control: u8,
+    lut: Option<Vec<u8>>,
+    refreshes: Vec<u8>,
+}
+
+impl EmulatorState {
+    const X_INCREMENT: u8 = 0b001;
+    const Y_INCREMENT: u8 = 0b010;
+    const Y_DIRECTION: u8 = 0b100;
+
+    /// Reset the registers to their power on values. The RAM is kept.
+    fn reset(&mut self) {
+        self.data_entry_mode = Self::X_INCREMENT | Self::Y_INCREMENT;
+        self.x_window = (0, self.bytes_per_line as u32 - 1);
+        self.y_window = (0, self.height as u32 - 1);
+        self.x_counter = 0;
+        self.y_counter = 0;
+        self.ram_options = 0;
+        self.lut = None;
+    }
+
+    fn command(&mut self, command: u8) {
+        self.command = Some(command);
+        self.parameters.clear();
+        match command {
+            // Deep sleep is left with a hardware reset, which resets the registers as well.
+            command::SW_RESET | command::DEEP_SLEEP => self.reset(),
+            command::MASTER_ACTIVATE => self.activate(),
+            _ => {}
+        }
+    }
+
+    fn data<CTRL: Controller>(&mut self, data: &[u8]) {
+        let x_bytes = CTRL::RAM_X_ADDRESS_BYTES;
+        let y_bytes = CTRL::RAM_Y_ADDRESS_BYTES;
+        match self.command {
+            Some(command::WRITE_BW_DATA) => {
+                data.iter().for_each(|byte| self.write_ram(*byte, false))
+            }
+            Some(command::WRITE_RED_DATA) => {
+                data.iter().for_each(|byte| self.write_ram(*byte, true))
+            }
+            Some(command) => {
+                self.parameters.extend_from_slice(data);
+                let parameters = &self.parameters;
+                match command {
+                    command::DATA_ENTRY_MODE if !parameters.is_empty() => {
+                        self.data_entry_mode = parameters[0] & 0b111;
+                    }
+                    command::SET_RAMXPOS if parameters.len() >= 2 * x_bytes => {
+                        self.x_window = (
+                            decode_address(&parameters[..x_bytes]),
+                            decode_address(&parameters[x_bytes..2 * x_bytes]),
+                        );
+                    }
+                    command::SET_RAMYPOS if parameters.len() >= 2 * y_bytes => {
+                        self.y_window = (
+                            decode_address(&parameters[..y_bytes]),
+                            decode_address(&parameters[y_bytes..2 * y_bytes]),
+                        );
+                    }
+                    command::SET_RAMX_COUNTER if parameters.len() >= x_bytes => {
+                        self.x_counter = decode_address(&parameters[..x_bytes]);
+                    }
+                    command::SET_RAMY_COUNTER if parameters.len() >= y_bytes => {
+                        self.y_counter = decode_address(&parameters[..y_bytes]);
+                    }
+                    command::DISPLAY_UPDATE_CONTROL if !parameters.is_empty() => {
+                        self.ram_options = parameters[0];
+                    }
+                    command::UPDATE_DISPLAY_CTRL2 if !parameters.is_empty() => {
+                        self.update_control = parameters[0];
+                    }
+                    command::WRITE_LUT => {
+                        self.lut = Some(parameters.clone());
+                    }
+                    _ => {}
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn write_ram(&mut self, byte: u8, red: bool) {
+        let (x, y) = (self.x_counter as usize, self.y_counter as usize);
+        if x < self.bytes_per_line && y < self.height {
+            let ram = if red {
+                &mut self.red_ram
+            } else {
+                &mut self.bw_ram
+            };
+            ram[y * self.bytes_per_line + x] = byte;
+        }
+
+        let x_increment = self.data_entry_mode & Self::X_INCREMENT != 0;
+        let y_increment = self.data_entry_mode & Self::Y_INCREMENT != 0;
+        if self.data_entry_mode & Self::Y_DIRECTION == 0 {
+            let wrapped;
+            (self.x_counter, wrapped) = step(self.x_counter, self.x_window, x_increment);
+            if wrapped {
+                (self.y_counter, _) = step(self.y_counter, self.y_window, y_increment);
+            }
+        } else {
+            let wrapped;
+            (self.y_counter, wrapped) = step(self.y_counter, self.y_window, y_increment);
+            if wrapped {
+                (self.x_counter, _) = step(self.x_counter, self.x_window, x_increment);
+            }
+        }
+    }
+
+    fn activate(&mut self) {
+        const DISPLAY: u8 = 0x04;
+        const BYPASS: u8 = 0b0100;
+        const INVERSE: u8 = 0b1000;
+
+        self.refreshes.push(self.update_control);
+        if self.update_control & DISPLAY == 0 {
+            return;
+        }
+        let apply = |ram: &[u8], displayed: &mut Vec<u8>, option: u8| {
+            displayed.clear();
+            displayed.extend(ram.iter().map(|byte| {
+                if option & BYPASS != 0 {
+                    0x00
+                } else if option & INVERSE != 0 {
+                    !byte
+                } else {
+                    *byte
+                }
+            }));
+        };
+        apply(
+            &self.bw_ram,
+            &mut self.displayed_bw,
+            self.ram_options & 0x0F,
+        );
+        apply(
+            &self.red_ram,
+            &mut self.displayed_red,
+            self.ram_options >> 4,
+        );
+    }
+
+    fn pixel(&self, plane: &[u8], x: u32, y: u32) -> bool {
+        assert!((y as usize) < self.height, "pixel is outside of the RAM");
+        let byte = plane[y as usize * self.bytes_per_line + (x / 8) as usize];
+        byte & (0x80 >> (x % 8)) != 0
+    }
+}
+
+/// Moves a RAM counter one address in the given direction, wrapping around at the end of the
+/// window. Returns whether the counter wrapped.
+fn step(counter: u32, (start, end): (u32, u32), increment: bool) -> (u32, bool) {
+    if counter == end {
+        (start, true)
+    } else if increment {
+        (counter.wrapping_add(1), false)
+    } else {
+        (counter.wrapping_sub(1), false)
+    }
+}
+
+fn decode_address(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0, |address, byte| address << 8 | u32::from(*byte))
+}
+
+/// Busy pin whose level can be scripted.
+///
+/// The pin is idle (low) unless told otherwise.
+#[derive(Debug, Clone, Default)]
+pub struct MockBusyPin {
+    busy_polls: Rc<Cell<u32>>,
+    stuck: Rc<Cell<bool>>,
+}
+
+impl MockBusyPin {
+    /// Create a new busy pin that is idle.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report busy for the next `polls` reads of the pin level.
+    ///
+    /// In async mode every poll of a future waiting for the pin counts as a read. Waiting for an
+    /// edge completes once two reads in a row see the pin change level.
+    pub fn busy_for(&self, polls: u32) {
+        self.busy_polls.set(polls);
+    }
+
+    /// Keep the pin busy until [`Self::release`] is called, like a disconnected display would.
+    pub fn stick(&self) {
+        self.stuck.set(true);
+    }
+
+    /// Make the pin idle again.
+    pub fn release(&self) {
+        self.stuck.set(false);
+        self.busy_polls.set(0);
+    }
+
+    fn poll_busy(&self) -> bool {
+        if self.stuck.get() {
+            return true;
+        }
+        let polls = self.busy_polls.get();
+        self.busy_polls.set(polls.saturating_sub(1));
+        polls > 0
+    }
+
+    /// Read the pin level on every poll until `done` returns `true` for the previous level, if
+    /// any, and the current one.
+    async fn wait_for(
+        &self,
+        mut done: impl FnMut(Option<bool>, bool) -> bool,
+    ) -> Result<(), Infallible> {
+        let mut previous = None;
+        core::future::poll_fn(|cx| {
+            let busy = self.poll_busy();
+            if done(previous, busy) {
+                Poll::Ready(Ok(()))
+            } else {
+                previous = Some(busy);
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl embedded_hal::digital::ErrorType for MockBusyPin {
+    type Error = Infallible;
+}
+
+impl embedded_hal::digital::InputPin for MockBusyPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.poll_busy())
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.poll_busy())
+    }
+}
+
+impl embedded_hal_async::digital::Wait for MockBusyPin {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(|_, busy| busy).await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(|_, busy| !busy).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(|previous, busy| previous == Some(false) && busy)
+            .await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(|previous, busy| previous == Some(true) && !busy)
+            .await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(|previous, busy| previous.is_some_and(|previous| previous != busy))
+            .await
+    }
+}
+
+/// Reset pin that records the levels it is set to.
+#[derive(Debug, Clone, Default)]
+pub struct MockResetPin {
+    levels: Rc<RefCell<Vec<bool>>>,
+}
+
+impl MockResetPin {
+    /// Create a new reset pin.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the levels the pin was set to, `true` being high.
+    pub fn levels(&self) -> Vec<bool> {
+        self.levels.borrow().clone()
+    }
+}
+
+impl embedded_hal::digital::ErrorType for MockResetPin {
+    type Error = Infallible;
+}
+
+impl embedded_hal::digital::OutputPin for MockResetPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.levels.borrow_mut().push(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.levels.borrow_mut().push(true);
+        Ok(())
+    }
+}
+
+/// Delay that doesn't wait and keeps track of the total requested delay.
+///
+/// In async mode the delay is pending for one poll per started millisecond, so a busy timeout
+/// only expires after the [`MockBusyPin`] was polled for about as many times.
+#[derive(Debug, Clone, Default)]
+pub struct MockDelay {
+    elapsed_ns: Rc<Cell<u64>>,
+}
+
+impl MockDelay {
+    /// Create a new delay.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Total delay requested so far in nanoseconds.
+    pub fn elapsed_ns(&self) -> u64 {
+        self.elapsed_ns.get()
+    }
+}
+
+impl embedded_hal::delay::DelayNs for MockDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.elapsed_ns.set(self.elapsed_ns.get() + u64::from(ns));
+    }
+}
+
+impl embedded_hal_async::delay::DelayNs for MockDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        let mut remaining_ns = ns;
+        core::future::poll_fn(|cx| {
+            if remaining_ns == 0 {
+                return Poll::Ready(());
+            }
+            let step = remaining_ns.min(1_000_000);
+            remaining_ns -= step;
+            self.elapsed_ns.set(self.elapsed_ns.get() + u64::from(step));
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+/// Run a future to completion by polling it in a loop.
+///
+/// Intended for the async driver together with the mocks in this module, which wake up the
+/// task right away when they are pending. Never returns if the future doesn't complete, for
+/// example when waiting for a [stuck](MockBusyPin::stick) busy pin without a busy timeout.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(emulator: &mut Emulator, command: u8, data: &[u8]) {
+        emulator.send_commands(DataFormat::U8(&[command])).unwrap();
+        emulator.send_data(DataFormat::U8(data)).unwrap();
+    }
+
+    #[test]
+    fn counter_wraps_inside_window() {
+        let mut emulator = Emulator::new();
+        send(&mut emulator, command::SET_RAMXPOS, &[1, 2]);
+        send(&mut emulator, command::SET_RAMYPOS, &[3, 0, 4, 0]);
+        send(&mut emulator, command::SET_RAMX_COUNTER, &[1]);
+        send(&mut emulator, command::SET_RAMY_COUNTER, &[3, 0]);
+
+        send(&mut emulator, command::WRITE_BW_DATA, &[1, 2, 3, 4, 5]);
+
+        // The last byte wraps around to the start of the window.
+        let ram = emulator.bw_ram();
+        assert_eq!(ram[3 * 22..3 * 22 + 4], [0xFF, 5, 2, 0xFF]);
+        assert_eq!(ram[4 * 22..4 * 22 + 4], [0xFF, 3, 4, 0xFF]);
+        assert_eq!(ram[5 * 22 + 1], 0xFF);
+    }
+
+    #[test]
+    fn decrementing_y_direction_data_entry() {
+        let mut emulator = Emulator::new();
+        // X increment, Y decrement, Y direction first.
+        send(&mut emulator, command::DATA_ENTRY_MODE, &[0b101]);
+        send(&mut emulator, command::SET_RAMXPOS, &[0, 1]);
+        send(&mut emulator, command::SET_RAMYPOS, &[1, 0, 0, 0]);
+        send(&mut emulator, command::SET_RAMX_COUNTER, &[0]);
+        send(&mut emulator, command::SET_RAMY_COUNTER, &[1, 0]);
+
+        send(&mut emulator, command::WRITE_BW_DATA, &[1, 2, 3, 4]);
+
+        let ram = emulator.bw_ram();
+        assert_eq!(ram[22..24], [1, 3]);
+        assert_eq!(ram[..2], [2, 4]);
+    }
+
+    #[test]
+    fn displayed_image() {
+        let mut emulator = Emulator::new();
+        send(&mut emulator, command::WRITE_BW_DATA, &[0x0F]);
+        send(&mut emulator, command::SET_RAMX_COUNTER, &[0]);
+        send(&mut emulator, command::WRITE_RED_DATA, &[0x80]);
+        send(&mut emulator, command::UPDATE_DISPLAY_CTRL2, &[0xF7]);
+        send(&mut emulator, command::MASTER_ACTIVATE, &[]);
+
+        let image = emulator.displayed_image(8, 1);
+        let encoded: Vec<u8> = crate::netpbm::encode(&image, crate::netpbm::Format::Pbm).collect();
+        assert_eq!(encoded, b"P4\n8 1\n\xF0");
+
+        let image = emulator.displayed_tri_color_image(8, 1);
+        assert_eq!(image.pixel(0, 0), [255, 0, 0]);
+        assert_eq!(image.pixel(1, 0), [0, 0, 0]);
+        assert_eq!(image.pixel(4, 0), [255, 255, 255]);
+    }
+
+    #[test]
+    fn refresh_without_display_keeps_image() {
+        let mut emulator = Emulator::new();
+        send(&mut emulator, command::WRITE_BW_DATA, &[0x00]);
+        send(&mut emulator, command::UPDATE_DISPLAY_CTRL2, &[0xB1]);
+        send(&mut emulator, command::MASTER_ACTIVATE, &[]);
+
+        assert_eq!(emulator.displayed_color(0, 0), Color::White);
+        assert_eq!(emulator.refreshes(), [0xB1]);
+    }
+
+    #[test]
+    fn busy_pin_waits_follow_the_script() {
+        use embedded_hal_async::digital::Wait;
+
+        let mut pin = MockBusyPin::new();
+        pin.busy_for(2);
+        block_on(pin.wait_for_low()).unwrap();
+        assert!(!pin.poll_busy());
+
+        pin.busy_for(1);
+        block_on(pin.wait_for_high()).unwrap();
+        assert!(!pin.poll_busy());
+
+        // Busy, busy, idle: the first change of level is a falling edge.
+        pin.busy_for(2);
+        block_on(pin.wait_for_falling_edge()).unwrap();
+        pin.busy_for(2);
+        block_on(pin.wait_for_any_edge()).unwrap();
+        assert!(!pin.poll_busy());
+    }
+}
@@ -0,0 +1,528 @@
+//! Encoding and decoding of Netpbm (PBM, PGM and PPM) images for snapshot testing.
+//!
+//! Only the binary variants (`P4`, `P5` and `P6`) with a maximum value of at most 255 are
+//! supported. The encoder is an iterator over the bytes of the image so it doesn't need any
+//! memory for the image.
+//!
+//! ```
+//! # #[cfg(feature = "graphics")]
+//! # {
+//! use embedded_graphics::{
+//!     prelude::*,
+//!     primitives::{PrimitiveStyle, Rectangle},
+//! };
+//! use weact_studio_epd::{
+//!     graphics::Display290TriColor,
+//!     netpbm::{self, Format, Image},
+//!     TriColor,
+//! };
+//!
+//! let mut display = Display290TriColor::new();
+//! Rectangle::new(Point::new(0, 0), Size::new(8, 8))
+//!     .into_styled(PrimitiveStyle::with_fill(TriColor::Red))
+//!     .draw(&mut display)
+//!     .unwrap();
+//!
+//! let ppm: Vec<u8> = netpbm::encode(&display, Format::Ppm).collect();
+//!
+//! let image = Image::decode(ppm).unwrap();
+//! assert_eq!(image.pixel(0, 0), [255, 0, 0]);
+//! assert_eq!(image.pixel(8, 0), [255, 255, 255]);
+//! # }
+//! ```
+
+use core::fmt;
+
+#[cfg(feature = "graphics")]
+use embedded_graphics::{
+    geometry::OriginDimensions,
+    pixelcolor::{PixelColor, Rgb888, RgbColor},
+    prelude::Point,
+};
+
+#[cfg(feature = "graphics")]
+use crate::{color::ColorType, graphics::Display};
+
+/// Netpbm image format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Black and white image (`P4`). Pixels darker than 50% gray are black.
+    Pbm,
+    /// Grayscale image (`P5`).
+    Pgm,
+    /// RGB image (`P6`).
+    Ppm,
+}
+
+/// Source of pixels for an image.
+pub trait PixelSource {
+    /// Width of the image in pixels.
+    fn width(&self) -> u32;
+
+    /// Height of the image in pixels.
+    fn height(&self) -> u32;
+
+    /// RGB value of the pixel at the given position.
+    ///
+    /// Only called for positions inside the image.
+    fn pixel(&self, x: u32, y: u32) -> [u8; 3];
+}
+
+impl<S: PixelSource + ?Sized> PixelSource for &S {
+    fn width(&self) -> u32 {
+        (**self).width()
+    }
+
+    fn height(&self) -> u32 {
+        (**self).height()
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        (**self).pixel(x, y)
+    }
+}
+
+/// The display as it is shown, i.e. with rotation applied.
+#[cfg_attr(docsrs, doc(cfg(feature = "graphics")))]
+#[cfg(feature = "graphics")]
+impl<
+        const WIDTH: u32,
+        const VISIBLE_WIDTH: u32,
+        const HEIGHT: u32,
+        const BUFFER_SIZE: usize,
+        C,
+    > PixelSource for Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>
+where
+    C: ColorType + PixelColor + Into<Rgb888>,
+{
+    fn width(&self) -> u32 {
+        self.size().width
+    }
+
+    fn height(&self) -> u32 {
+        self.size().height
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        let color: Rgb888 = self
+            .get_pixel(Point::new(x as i32, y as i32))
+            .map_or(Rgb888::WHITE, Into::into);
+        [color.r(), color.g(), color.b()]
+    }
+}
+
+/// Encode `source` as a binary Netpbm image of the given format.
+///
+/// Returns an iterator over the bytes of the image file.
+pub fn encode<S: PixelSource>(source: S, format: Format) -> Encoder<S> {
+    Encoder {
+        header: Header::new(format, source.width(), source.height()),
+        source,
+        format,
+        index: 0,
+    }
+}
+
+/// Iterator over the bytes of an encoded image, created with [`encode`].
+pub struct Encoder<S> {
+    source: S,
+    format: Format,
+    header: Header,
+    index: usize,
+}
+
+impl<S: PixelSource> Encoder<S> {
+    fn data_len(&self) -> usize {
+        let (width, height) = (self.source.width() as usize, self.source.height() as usize);
+        match self.format {
+            Format::Pbm => width.div_ceil(8) * height,
+            Format::Pgm => width * height,
+            Format::Ppm => width * height * 3,
+        }
+    }
+
+    fn data_byte(&self, index: usize) -> u8 {
+        let width = self.source.width() as usize;
+        match self.format {
+            Format::Pbm => {
+                let bytes_per_line = width.div_ceil(8);
+                let (y, first_x) = (index / bytes_per_line, index % bytes_per_line * 8);
+                (first_x..(first_x + 8).min(width))
+                    .filter(|&x| luma(self.source.pixel(x as u32, y as u32)) < 128)
+                    .fold(0, |byte, x| byte | 0x80 >> (x - first_x))
+            }
+            Format::Pgm => luma(
+                self.source
+                    .pixel((index % width) as u32, (index / width) as u32),
+            ),
+            Format::Ppm => {
+                let pixel = index / 3;
+                self.source
+                    .pixel((pixel % width) as u32, (pixel / width) as u32)[index % 3]
+            }
+        }
+    }
+}
+
+impl<S: PixelSource> Iterator for Encoder<S> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let index = self.index;
+        let byte = if index < self.header.len {
+            self.header.bytes[index]
+        } else if index - self.header.len < self.data_len() {
+            self.data_byte(index - self.header.len)
+        } else {
+            return None;
+        };
+        self.index += 1;
+        Some(byte)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.header.len + self.data_len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<S: PixelSource> ExactSizeIterator for Encoder<S> {}
+
+/// Image header, long enough for two u32 dimensions.
+#[derive(Default)]
+struct Header {
+    bytes: [u8; 32],
+    len: usize,
+}
+
+impl Header {
+    fn new(format: Format, width: u32, height: u32) -> Self {
+        let mut header = Self::default();
+        // The longest header, "P6\n4294967295 4294967295\n255\n", is 29 bytes, so writing can't
+        // fail for any u32 dimensions.
+        match format {
+            Format::Pbm => fmt::write(&mut header, format_args!("P4\n{width} {height}\n")),
+            Format::Pgm => fmt::write(&mut header, format_args!("P5\n{width} {height}\n255\n")),
+            Format::Ppm => fmt::write(&mut header, format_args!("P6\n{width} {height}\n255\n")),
+        }
+        .expect("header fits");
+        header
+    }
+}
+
+impl fmt::Write for Header {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Perceived brightness of an RGB value.
+fn luma([r, g, b]: [u8; 3]) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000) as u8
+}
+
+/// Error decoding an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The image isn't a binary PBM, PGM or PPM image, or uses more than 8 bits per sample.
+    Unsupported,
+    /// The image header is malformed.
+    InvalidHeader,
+    /// The image has less data than its header requires.
+    MissingData,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Unsupported => f.write_str("unsupported image format"),
+            DecodeError::InvalidHeader => f.write_str("invalid image header"),
+            DecodeError::MissingData => f.write_str("image data is incomplete"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Decoded binary Netpbm image, for example a golden image to compare against.
+///
+/// The image refers to the encoded data, `D` can be anything that derefs to bytes.
+#[derive(Debug, Clone)]
+pub struct Image<D> {
+    data: D,
+    format: Format,
+    width: u32,
+    height: u32,
+    max_value: u8,
+    offset: usize,
+}
+
+impl<D: AsRef<[u8]>> Image<D> {
+    /// Decode the image in `data`.
+    pub fn decode(data: D) -> Result<Self, DecodeError> {
+        let bytes = data.as_ref();
+        let format = match bytes.get(..2) {
+            Some(b"P4") => Format::Pbm,
+            Some(b"P5") => Format::Pgm,
+            Some(b"P6") => Format::Ppm,
+            _ => return Err(DecodeError::Unsupported),
+        };
+        let mut offset = 2;
+        let width = parse_header_value(bytes, &mut offset)?;
+        let height = parse_header_value(bytes, &mut offset)?;
+        let max_value = match format {
+            Format::Pbm => 1,
+            Format::Pgm | Format::Ppm => parse_header_value(bytes, &mut offset)?,
+        };
+        if max_value == 0 {
+            return Err(DecodeError::InvalidHeader);
+        }
+        let max_value = u8::try_from(max_value).map_err(|_| DecodeError::Unsupported)?;
+        // A single whitespace character separates the header from the data.
+        if !bytes.get(offset).is_some_and(u8::is_ascii_whitespace) {
+            return Err(DecodeError::InvalidHeader);
+        }
+        offset += 1;
+
+        let image = Self {
+            data,
+            format,
+            width,
+            height,
+            max_value,
+            offset,
+        };
+        let (width, height) = (width as usize, height as usize);
+        // The header comes from untrusted input, so the size can overflow on 32-bit targets.
+        let data_end = match format {
+            Format::Pbm => width.div_ceil(8).checked_mul(height),
+            Format::Pgm => width.checked_mul(height),
+            Format::Ppm => width
+                .checked_mul(height)
+                .and_then(|pixels| pixels.checked_mul(3)),
+        }
+        .and_then(|data_len| data_len.checked_add(offset))
+        .ok_or(DecodeError::InvalidHeader)?;
+        if image.data.as_ref().len() < data_end {
+            return Err(DecodeError::MissingData);
+        }
+        Ok(image)
+    }
+
+    /// Format of the image.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// RGB value of the pixel at the given position.
+    ///
+    /// Panics if the position is outside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        assert!(
+            x < self.width && y < self.height,
+            "pixel is outside of the image"
+        );
+        let data = &self.data.as_ref()[self.offset..];
+        let (x, y, width) = (x as usize, y as usize, self.width as usize);
+        let scale = |value: u8| (value as u32 * 255 / self.max_value as u32) as u8;
+        match self.format {
+            Format::Pbm => {
+                let byte = data[y * width.div_ceil(8) + x / 8];
+                if byte & (0x80 >> (x % 8)) != 0 {
+                    [0, 0, 0]
+                } else {
+                    [255, 255, 255]
+                }
+            }
+            Format::Pgm => {
+                let value = scale(data[y * width + x]);
+                [value, value, value]
+            }
+            Format::Ppm => {
+                let index = (y * width + x) * 3;
+                [
+                    scale(data[index]),
+                    scale(data[index + 1]),
+                    scale(data[index + 2]),
+                ]
+            }
+        }
+    }
+}
+
+impl<D: AsRef<[u8]>> PixelSource for Image<D> {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        Image::pixel(self, x, y)
+    }
+}
+
+/// Parses a decimal header value after skipping whitespace and comments.
+fn parse_header_value(bytes: &[u8], offset: &mut usize) -> Result<u32, DecodeError> {
+    loop {
+        match bytes.get(*offset) {
+            Some(byte) if byte.is_ascii_whitespace() => *offset += 1,
+            Some(b'#') => {
+                while bytes.get(*offset).is_some_and(|byte| *byte != b'\n') {
+                    *offset += 1;
+                }
+            }
+            _ => break,
+        }
+    }
+    let start = *offset;
+    let mut value: u32 = 0;
+    while let Some(byte) = bytes.get(*offset).filter(|byte| byte.is_ascii_digit()) {
+        value = value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(u32::from(byte - b'0')))
+            .ok_or(DecodeError::InvalidHeader)?;
+        *offset += 1;
+    }
+    if *offset == start {
+        return Err(DecodeError::InvalidHeader);
+    }
+    Ok(value)
+}
+
+/// Encode `source` and write it to the file at `path`.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub fn save<S: PixelSource>(
+    path: impl AsRef<std::path::Path>,
+    source: S,
+    format: Format,
+) -> std::io::Result<()> {
+    let bytes: std::vec::Vec<u8> = encode(source, format).collect();
+    std::fs::write(path, bytes)
+}
+
+/// Read and decode the image file at `path`.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Image<std::vec::Vec<u8>>> {
+    let bytes = std::fs::read(path)?;
+    Image::decode(bytes)
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Checkerboard;
+
+    impl PixelSource for Checkerboard {
+        fn width(&self) -> u32 {
+            10
+        }
+
+        fn height(&self) -> u32 {
+            2
+        }
+
+        fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+            if (x + y).is_multiple_of(2) {
+                [0, 0, 0]
+            } else {
+                [255, 0, 0]
+            }
+        }
+    }
+
+    fn encoded<const N: usize>(format: Format) -> [u8; N] {
+        let encoder = encode(Checkerboard, format);
+        assert_eq!(encoder.len(), N);
+        let mut bytes = [0; N];
+        bytes
+            .iter_mut()
+            .zip(encoder)
+            .for_each(|(byte, b)| *byte = b);
+        bytes
+    }
+
+    #[test]
+    fn pbm_rows_are_padded() {
+        let pbm: [u8; 12] = encoded(Format::Pbm);
+
+        assert_eq!(&pbm[..8], b"P4\n10 2\n");
+        // Red is darker than 50% gray.
+        assert_eq!(pbm[8..], [0xFF, 0xC0, 0xFF, 0xC0]);
+    }
+
+    #[test]
+    fn header_fits_largest_dimensions() {
+        let header = Header::new(Format::Ppm, u32::MAX, u32::MAX);
+        assert_eq!(
+            header.bytes[..header.len],
+            *b"P6\n4294967295 4294967295\n255\n"
+        );
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let ppm: [u8; 72] = encoded(Format::Ppm);
+        let image = Image::decode(&ppm[..]).unwrap();
+
+        assert_eq!(image.format(), Format::Ppm);
+        for y in 0..2 {
+            for x in 0..10 {
+                assert_eq!(image.pixel(x, y), Checkerboard.pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn decode_with_comments_and_max_value() {
+        let pgm = b"P5\n# comment\n2 1 # another one\n15\n\x00\x0F";
+        let image = Image::decode(&pgm[..]).unwrap();
+
+        assert_eq!(image.pixel(0, 0), [0, 0, 0]);
+        assert_eq!(image.pixel(1, 0), [255, 255, 255]);
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            Image::decode(&b"P1\n1 1\n1"[..]).unwrap_err(),
+            DecodeError::Unsupported
+        );
+        assert_eq!(
+            Image::decode(&b"P6\n1 1\n65535\n"[..]).unwrap_err(),
+            DecodeError::Unsupported
+        );
+        assert_eq!(
+            Image::decode(&b"P5\nx 1\n255\n"[..]).unwrap_err(),
+            DecodeError::InvalidHeader
+        );
+        assert_eq!(
+            Image::decode(&b"P5 2 2 255X\x00\x00\x00\x00"[..]).unwrap_err(),
+            DecodeError::InvalidHeader
+        );
+        assert_eq!(
+            Image::decode(&b"P4\n8 1"[..]).unwrap_err(),
+            DecodeError::InvalidHeader
+        );
+        assert_eq!(
+            Image::decode(&b"P5\n2 2\n255\n\x00"[..]).unwrap_err(),
+            DecodeError::MissingData
+        );
+        assert_eq!(
+            Image::decode(&b"P6\n4294967295 4294967295\n255\n"[..]).unwrap_err(),
+            DecodeError::InvalidHeader
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{Controller, Ssd1680, Ssd1681, Ssd1683};
+
+/// Geometry of a display panel and the controller it is connected to.
+///
+/// The display-specific driver and buffer aliases, like
+/// [`WeActStudio290BlackWhiteDriver`](crate::WeActStudio290BlackWhiteDriver), are derived from
+/// the panels in this crate. Implement this trait to describe panels that aren't supported out of
+/// the box and use its constants for the const generics of [`DisplayDriver`](crate::DisplayDriver)
+/// and [`Display`](crate::graphics::Display):
+///
+/// ```
+/// use weact_studio_epd::{Color, DisplayDriver, Panel, Ssd1680};
+///
+/// struct MyPanel;
+///
+/// impl Panel for MyPanel {
+///     const WIDTH: u32 = 176;
+///     const VISIBLE_WIDTH: u32 = 176;
+///     const HEIGHT: u32 = 264;
+///     type Controller = Ssd1680;
+/// }
+///
+/// type MyDisplayDriver<DI, BSY, RST, DELAY> = DisplayDriver<
+///     DI,
+///     BSY,
+///     RST,
+///     DELAY,
+///     { MyPanel::WIDTH },
+///     { MyPanel::VISIBLE_WIDTH },
+///     { MyPanel::HEIGHT },
+///     Color,
+///     <MyPanel as Panel>::Controller,
+/// >;
+/// ```
+pub trait Panel {
+    /// Width of the controller RAM used for the panel in pixels. Must be a multiple of 8.
+    const WIDTH: u32;
+
+    /// Number of columns that are visible on the panel.
+    const VISIBLE_WIDTH: u32;
+
+    /// Height of the panel in pixels.
+    const HEIGHT: u32;
+
+    /// Controller the panel is connected to.
+    type Controller: Controller;
+}
+
+/// WeAct Studio 1.54 inch 200x200 panel.
+pub struct WeActStudio154;
+
+impl Panel for WeActStudio154 {
+    const WIDTH: u32 = 200;
+    const VISIBLE_WIDTH: u32 = 200;
+    const HEIGHT: u32 = 200;
+    type Controller = Ssd1681;
+}
+
+/// WeAct Studio 2.13 inch 122x250 panel, used by the B/W and tri-color displays.
+///
+/// The controller uses 128 columns of RAM but only 122 of them are visible.
+pub struct WeActStudio213;
+
+impl Panel for WeActStudio213 {
+    const WIDTH: u32 = 128;
+    const VISIBLE_WIDTH: u32 = 122;
+    const HEIGHT: u32 = 250;
+    type Controller = Ssd1680;
+}
+
+/// WeAct Studio 2.9 inch 128x296 panel, used by the B/W and tri-color displays.
+pub struct WeActStudio290;
+
+impl Panel for WeActStudio290 {
+    const WIDTH: u32 = 128;
+    const VISIBLE_WIDTH: u32 = 128;
+    const HEIGHT: u32 = 296;
+    type Controller = Ssd1680;
+}
+
+/// WeAct Studio 4.2 inch 400x300 panel.
+pub struct WeActStudio420;
+
+impl Panel for WeActStudio420 {
+    const WIDTH: u32 = 400;
+    const VISIBLE_WIDTH: u32 = 400;
+    const HEIGHT: u32 = 300;
+    type Controller = Ssd1683;
+}
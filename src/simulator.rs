@@ -0,0 +1,595 @@
+//! Simulated display driver to try out layouts without hardware.
+//!
+//! [`SimulatedDriver`] has the same update methods as [`DisplayDriver`](crate::DisplayDriver)
+//! but draws the resulting panel image to an `embedded-graphics` draw target, usually a
+//! `SimulatorDisplay<Rgb888>` from the
+//! [`embedded-graphics-simulator`](https://crates.io/crates/embedded-graphics-simulator) crate.
+//!
+//! ```no_run
+//! use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+//! use embedded_graphics_simulator::{OutputSettings, SimulatorDisplay, Window};
+//! use weact_studio_epd::{graphics::Display213TriColor, simulator::WeActStudio213SimulatedDriver};
+//!
+//! let mut driver = WeActStudio213SimulatedDriver::new(SimulatorDisplay::<Rgb888>::new(
+//!     WeActStudio213SimulatedDriver::<SimulatorDisplay<Rgb888>>::size(Default::default()),
+//! ));
+//! let mut window = Window::new("WeAct Studio 2.13", &OutputSettings::default());
+//!
+//! let display = Display213TriColor::new();
+//! // Draw to `display`...
+//! driver.full_update(&display).unwrap();
+//! window.show_static(driver.target());
+//! ```
+
+use std::{boxed::Box, vec, vec::Vec};
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Point, Size},
+    pixelcolor::{PixelColor, Rgb888, RgbColor},
+    primitives::Rectangle,
+    Pixel,
+};
+
+use crate::{
+    color::ColorType,
+    graphics::{rotated_position, Display, DisplayRotation, Viewport},
+    Panel, WeActStudio154, WeActStudio213, WeActStudio290, WeActStudio420,
+};
+
+/// Simulated driver for the given [`Panel`].
+macro_rules! panel_simulated_driver {
+    ($panel:ty) => {
+        SimulatedDriver<
+            T,
+            { <$panel>::WIDTH },
+            { <$panel>::VISIBLE_WIDTH },
+            { <$panel>::HEIGHT },
+        >
+    };
+}
+
+/// Simulated driver for the WeAct Studio 1.54 inch display.
+pub type WeActStudio154SimulatedDriver<T> = panel_simulated_driver!(WeActStudio154);
+/// Simulated driver for the WeAct Studio 2.9 inch displays.
+pub type WeActStudio290SimulatedDriver<T> = panel_simulated_driver!(WeActStudio290);
+/// Simulated driver for the WeAct Studio 2.13 inch displays.
+pub type WeActStudio213SimulatedDriver<T> = panel_simulated_driver!(WeActStudio213);
+/// Simulated driver for the WeAct Studio 4.2 inch display.
+pub type WeActStudio420SimulatedDriver<T> = panel_simulated_driver!(WeActStudio420);
+
+/// Display driver that draws to an `embedded-graphics` draw target instead of a display.
+///
+/// The const generics have the same meaning as for [`DisplayDriver`](crate::DisplayDriver).
+/// Only the first `VISIBLE_WIDTH` columns of the buffer are drawn, rotated according to the
+/// rotation of the last [`Display`] passed to [`Self::full_update`] or [`Self::fast_update`].
+///
+/// Like on the real display, fast updates of tri-color displays show red pixels as white until
+/// the next full update.
+pub struct SimulatedDriver<T, const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32> {
+    target: T,
+    rotation: DisplayRotation,
+    /// Image in the display RAM, in buffer coordinates.
+    pixels: Vec<Rgb888>,
+    /// Image shown on the display, including ghosting.
+    shown: Vec<Rgb888>,
+    show_frame: Option<FrameCallback<T>>,
+}
+
+type FrameCallback<T> = Box<dyn FnMut(&T)>;
+
+impl<T, const WIDTH: u32, const VISIBLE_WIDTH: u32, const HEIGHT: u32>
+    SimulatedDriver<T, WIDTH, VISIBLE_WIDTH, HEIGHT>
+where
+    T: DrawTarget<Color = Rgb888>,
+{
+    /// Share of the previous color that remains visible after a fast refresh when animating.
+    const GHOSTING_PERCENT: u32 = 15;
+
+    /// Create a new simulated driver that draws to `target`.
+    ///
+    /// The display starts out white.
+    pub fn new(target: T) -> Self {
+        let len = (WIDTH * HEIGHT) as usize;
+        Self {
+            target,
+            rotation: DisplayRotation::default(),
+            pixels: vec![Rgb888::WHITE; len],
+            shown: vec![Rgb888::WHITE; len],
+            show_frame: None,
+        }
+    }
+
+    /// Size of the visible part of the display with the given rotation.
+    ///
+    /// Use this to create a draw target of the right size.
+    pub fn size(rotation: DisplayRotation) -> Size {
+        match rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                Size::new(VISIBLE_WIDTH, HEIGHT)
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                Size::new(HEIGHT, VISIBLE_WIDTH)
+            }
+        }
+    }
+
+    /// Get the draw target.
+    pub fn target(&self) -> &T {
+        &self.target
+    }
+
+    /// Get the draw target mutably.
+    pub fn target_mut(&mut self) -> &mut T {
+        &mut self.target
+    }
+
+    /// Consume the driver and return the draw target.
+    pub fn into_target(self) -> T {
+        self.target
+    }
+
+    /// Set the rotation used to draw the display.
+    ///
+    /// Full and fast updates take the rotation from the [`Display`] so this is only needed when
+    /// using partial updates.
+    pub fn set_rotation(&mut self, rotation: DisplayRotation) {
+        self.rotation = rotation;
+    }
+
+    /// Animate refreshes by calling `show_frame` with the draw target for every intermediate
+    /// frame, for example to update a simulator window.
+    ///
+    /// Full refreshes flash the display and fast refreshes leave some ghosting of the previous
+    /// image, which is removed again by the next full refresh.
+    pub fn set_animation(&mut self, show_frame: impl FnMut(&T) + 'static) {
+        self.show_frame = Some(Box::new(show_frame));
+    }
+
+    /// Update the screen with the provided [`Display`] using a full refresh.
+    pub fn full_update<const BUFFER_SIZE: usize, C>(
+        &mut self,
+        display: &Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>,
+    ) -> Result<(), T::Error>
+    where
+        C: ColorType + PixelColor + Into<Rgb888>,
+    {
+        self.rotation = display.rotation();
+        self.write(display, Self::full_frame(), 0, 0);
+        self.full_refresh()
+    }
+
+    /// Update the screen with the provided [`Display`] using a fast refresh.
+    pub fn fast_update<const BUFFER_SIZE: usize, C>(
+        &mut self,
+        display: &Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>,
+    ) -> Result<(), T::Error>
+    where
+        C: ColorType + PixelColor + Into<Rgb888>,
+    {
+        self.rotation = display.rotation();
+        self.write(display, Self::full_frame(), 0, 0);
+        self.fast_refresh()
+    }
+
+    /// Update the changed part of the screen with the provided [`Display`] using a fast refresh.
+    ///
+    /// Only the [`Display::dirty_area`] is copied, after which the dirty state of `display` is
+    /// cleared. Does nothing if nothing changed.
+    pub fn fast_update_dirty<const BUFFER_SIZE: usize, C>(
+        &mut self,
+        display: &mut Display<WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>,
+    ) -> Result<(), T::Error>
+    where
+        C: ColorType + PixelColor + Into<Rgb888>,
+    {
+        let Some(area) = display.dirty_area() else {
+            return Ok(());
+        };
+        self.rotation = display.rotation();
+        self.write(
+            display,
+            area,
+            area.top_left.x as u32,
+            area.top_left.y as u32,
+        );
+        display.clear_dirty();
+        self.fast_refresh()
+    }
+
+    /// Update the part of the screen covered by the provided [`Viewport`] using a fast refresh.
+    ///
+    /// Does nothing if the viewport is empty.
+    pub fn fast_update_viewport<const BUFFER_SIZE: usize, C>(
+        &mut self,
+        viewport: &Viewport<'_, WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>,
+    ) -> Result<(), T::Error>
+    where
+        C: ColorType + PixelColor + Into<Rgb888>,
+    {
+        if self.write_viewport(viewport) {
+            self.fast_refresh()?;
+        }
+        Ok(())
+    }
+
+    /// Update the part of the screen covered by the provided [`Viewport`] using a full refresh.
+    ///
+    /// Does nothing if the viewport is empty.
+    pub fn full_update_viewport<const BUFFER_SIZE: usize, C>(
+        &mut self,
+        viewport: &Viewport<'_, WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>,
+    ) -> Result<(), T::Error>
+    where
+        C: ColorType + PixelColor + Into<Rgb888>,
+    {
+        if self.write_viewport(viewport) {
+            self.full_refresh()?;
+        }
+        Ok(())
+    }
+
+    /// Update the screen with the provided partial [`Display`] at the given buffer position
+    /// using a full refresh.
+    ///
+    /// Parts of `display` that don't fit on the screen are ignored.
+    pub fn full_partial_update<
+        const W: u32,
+        const VW: u32,
+        const H: u32,
+        const BUFFER_SIZE: usize,
+        C,
+    >(
+        &mut self,
+        display: &Display<W, VW, H, BUFFER_SIZE, C>,
+        x: u32,
+        y: u32,
+    ) -> Result<(), T::Error>
+    where
+        C: ColorType + PixelColor + Into<Rgb888>,
+    {
+        let area = Rectangle::new(Point::zero(), Size::new(W, H));
+        self.write(display, area, x, y);
+        self.full_refresh()
+    }
+
+    /// Update the screen with the provided partial [`Display`] at the given buffer position
+    /// using a fast refresh.
+    ///
+    /// Parts of `display` that don't fit on the screen are ignored.
+    pub fn fast_partial_update<
+        const W: u32,
+        const VW: u32,
+        const H: u32,
+        const BUFFER_SIZE: usize,
+        C,
+    >(
+        &mut self,
+        display: &Display<W, VW, H, BUFFER_SIZE, C>,
+        x: u32,
+        y: u32,
+    ) -> Result<(), T::Error>
+    where
+        C: ColorType + PixelColor + Into<Rgb888>,
+    {
+        let area = Rectangle::new(Point::zero(), Size::new(W, H));
+        self.write(display, area, x, y);
+        self.fast_refresh()
+    }
+
+    fn full_frame() -> Rectangle {
+        Rectangle::new(Point::zero(), Size::new(WIDTH, HEIGHT))
+    }
+
+    /// Copy the [`Viewport::buffer_area`] of `viewport` to the RAM image.
+    ///
+    /// Returns `false` if the viewport is empty.
+    fn write_viewport<const BUFFER_SIZE: usize, C>(
+        &mut self,
+        viewport: &Viewport<'_, WIDTH, VISIBLE_WIDTH, HEIGHT, BUFFER_SIZE, C>,
+    ) -> bool
+    where
+        C: ColorType + PixelColor + Into<Rgb888>,
+    {
+        let Some(area) = viewport.buffer_area() else {
+            return false;
+        };
+        let display = viewport.display();
+        self.rotation = display.rotation();
+        let (x, y) = (area.top_left.x as u32, area.top_left.y as u32);
+        self.write(display, area, x, y);
+        true
+    }
+
+    /// Copy the `area` of the buffer of `display` to the RAM image at the given position.
+    fn write<const W: u32, const VW: u32, const H: u32, const BUFFER_SIZE: usize, C>(
+        &mut self,
+        display: &Display<W, VW, H, BUFFER_SIZE, C>,
+        area: Rectangle,
+        x: u32,
+        y: u32,
+    ) where
+        C: ColorType + PixelColor + Into<Rgb888>,
+    {
+        let (area_x, area_y) = (area.top_left.x as u32, area.top_left.y as u32);
+        let width = area.size.width.min(WIDTH.saturating_sub(x));
+        let height = area.size.height.min(HEIGHT.saturating_sub(y));
+        for dy in 0..height {
+            for dx in 0..width {
+                let color = display.buffer_pixel(area_x + dx, area_y + dy).into();
+                self.pixels[((y + dy) * WIDTH + x + dx) as usize] = color;
+            }
+        }
+    }
+
+    fn full_refresh(&mut self) -> Result<(), T::Error> {
+        if self.show_frame.is_some() {
+            let inverted: Vec<_> = self.shown.iter().map(|color| invert(*color)).collect();
+            self.show(&inverted)?;
+            self.show(&vec![Rgb888::BLACK; self.pixels.len()])?;
+            self.show(&vec![Rgb888::WHITE; self.pixels.len()])?;
+        }
+        self.shown.clone_from(&self.pixels);
+        self.draw(&self.shown.clone())
+    }
+
+    fn fast_refresh(&mut self) -> Result<(), T::Error> {
+        // Fast refreshes only drive pixels to black or white. Red stays in the RAM and is shown
+        // again by the next full refresh.
+        let new: Vec<_> = self
+            .pixels
+            .iter()
+            .map(|color| {
+                if *color == Rgb888::RED {
+                    Rgb888::WHITE
+                } else {
+                    *color
+                }
+            })
+            .collect();
+        if self.show_frame.is_some() {
+            let halfway: Vec<_> = self
+                .shown
+                .iter()
+                .zip(&new)
+                .map(|(shown, new)| blend(*shown, *new, 50))
+                .collect();
+            self.show(&halfway)?;
+            for (shown, new) in self.shown.iter_mut().zip(&new) {
+                if shown != new {
+                    *shown = blend(*shown, *new, Self::GHOSTING_PERCENT);
+                }
+            }
+        } else {
+            self.shown = new;
+        }
+        self.draw(&self.shown.clone())
+    }
+
+    /// Draw an intermediate frame and pass it to the animation callback.
+    fn show(&mut self, pixels: &[Rgb888]) -> Result<(), T::Error> {
+        self.draw(pixels)?;
+        if let Some(show_frame) = &mut self.show_frame {
+            show_frame(&self.target);
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, pixels: &[Rgb888]) -> Result<(), T::Error> {
+        let rotation = self.rotation;
+        let visible = (0..HEIGHT).flat_map(|y| (0..VISIBLE_WIDTH).map(move |x| (x, y)));
+        self.target.draw_iter(visible.map(|(x, y)| {
+            let (rotated_x, rotated_y) = rotated_position(x, y, VISIBLE_WIDTH, HEIGHT, rotation);
+            Pixel(
+                Point::new(rotated_x as i32, rotated_y as i32),
+                pixels[(y * WIDTH + x) as usize],
+            )
+        }))
+    }
+}
+
+fn invert(color: Rgb888) -> Rgb888 {
+    Rgb888::new(255 - color.r(), 255 - color.g(), 255 - color.b())
+}
+
+/// Mix `percent` of `from` into `to`.
+fn blend(from: Rgb888, to: Rgb888, percent: u32) -> Rgb888 {
+    let mix =
+        |from: u8, to: u8| ((from as u32 * percent + to as u32 * (100 - percent)) / 100) as u8;
+    Rgb888::new(
+        mix(from.r(), to.r()),
+        mix(from.g(), to.g()),
+        mix(from.b(), to.b()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+    use std::rc::Rc;
+
+    use embedded_graphics::{geometry::OriginDimensions, Drawable};
+
+    use super::*;
+    use crate::{
+        graphics::{
+            Display213TriColor, Display290BlackWhite, Display290GrayLevel, DisplayBlackWhite,
+        },
+        Color, GrayLevel, TriColor,
+    };
+
+    /// Draw target that stores the drawn pixels.
+    struct Framebuffer {
+        size: Size,
+        pixels: Vec<Rgb888>,
+    }
+
+    impl Framebuffer {
+        fn new(size: Size) -> Self {
+            Self {
+                size,
+                pixels: vec![Rgb888::BLACK; (size.width * size.height) as usize],
+            }
+        }
+
+        fn pixel(&self, x: u32, y: u32) -> Rgb888 {
+            self.pixels[(y * self.size.width + x) as usize]
+        }
+    }
+
+    impl OriginDimensions for Framebuffer {
+        fn size(&self) -> Size {
+            self.size
+        }
+    }
+
+    impl DrawTarget for Framebuffer {
+        type Color = Rgb888;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                assert!(
+                    (point.x as u32) < self.size.width && (point.y as u32) < self.size.height,
+                    "{point:?} is outside of the target"
+                );
+                self.pixels[(point.y as u32 * self.size.width + point.x as u32) as usize] = color;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rotated_display_matches_display_coordinates() {
+        let size = WeActStudio213SimulatedDriver::<Framebuffer>::size(DisplayRotation::Rotate90);
+        assert_eq!(size, Size::new(250, 122));
+        let mut driver = WeActStudio213SimulatedDriver::new(Framebuffer::new(size));
+        let mut display = Display213TriColor::new();
+        display.set_rotation(DisplayRotation::Rotate90);
+        Pixel(Point::new(0, 0), TriColor::Red)
+            .draw(&mut display)
+            .unwrap();
+        Pixel(Point::new(249, 121), TriColor::Black)
+            .draw(&mut display)
+            .unwrap();
+
+        driver.full_update(&display).unwrap();
+
+        let target = driver.target();
+        assert_eq!(target.pixel(0, 0), Rgb888::RED);
+        assert_eq!(target.pixel(1, 0), Rgb888::WHITE);
+        assert_eq!(target.pixel(249, 121), Rgb888::BLACK);
+    }
+
+    #[test]
+    fn fast_update_shows_red_as_white() {
+        let size = WeActStudio213SimulatedDriver::<Framebuffer>::size(DisplayRotation::Rotate0);
+        let mut driver = WeActStudio213SimulatedDriver::new(Framebuffer::new(size));
+        let mut display = Display213TriColor::new();
+        Pixel(Point::new(0, 0), TriColor::Red)
+            .draw(&mut display)
+            .unwrap();
+
+        driver.fast_update(&display).unwrap();
+        assert_eq!(driver.target().pixel(0, 0), Rgb888::WHITE);
+
+        driver.full_update(&display).unwrap();
+        assert_eq!(driver.target().pixel(0, 0), Rgb888::RED);
+    }
+
+    #[test]
+    fn full_refresh_of_other_area_shows_red_again() {
+        let size = WeActStudio213SimulatedDriver::<Framebuffer>::size(DisplayRotation::Rotate0);
+        let mut driver = WeActStudio213SimulatedDriver::new(Framebuffer::new(size));
+        let mut display = Display213TriColor::new();
+        Pixel(Point::new(0, 0), TriColor::Red)
+            .draw(&mut display)
+            .unwrap();
+
+        driver.fast_update(&display).unwrap();
+        assert_eq!(driver.target().pixel(0, 0), Rgb888::WHITE);
+
+        let viewport = display.viewport(Rectangle::new(Point::new(40, 50), Size::new(8, 2)));
+        driver.full_update_viewport(&viewport).unwrap();
+        assert_eq!(driver.target().pixel(0, 0), Rgb888::RED);
+    }
+
+    #[test]
+    fn animation_shows_intermediate_frames_and_ghosting() {
+        let size = WeActStudio290SimulatedDriver::<Framebuffer>::size(DisplayRotation::Rotate0);
+        let mut driver = WeActStudio290SimulatedDriver::new(Framebuffer::new(size));
+        let frames = Rc::new(core::cell::Cell::new(0));
+        let counter = frames.clone();
+        driver.set_animation(move |_| counter.set(counter.get() + 1));
+        let mut partial = DisplayBlackWhite::<8, 8, 8>::new();
+        partial.clear(Color::Black);
+
+        driver
+            .full_update(&DisplayBlackWhite::<128, 296, 4736>::new())
+            .unwrap();
+        assert_eq!(frames.get(), 3);
+
+        driver.fast_partial_update(&partial, 8, 16).unwrap();
+        assert_eq!(frames.get(), 4);
+        let ghost = driver.target().pixel(8, 16);
+        assert!(ghost != Rgb888::BLACK && ghost.r() < 128);
+        assert_eq!(driver.target().pixel(0, 16), Rgb888::WHITE);
+
+        driver
+            .full_update(&DisplayBlackWhite::<128, 296, 4736>::new())
+            .unwrap();
+        assert_eq!(driver.target().pixel(8, 16), Rgb888::WHITE);
+    }
+
+    #[test]
+    fn dirty_and_viewport_updates_only_copy_their_area() {
+        let size = WeActStudio290SimulatedDriver::<Framebuffer>::size(DisplayRotation::Rotate0);
+        let mut driver = WeActStudio290SimulatedDriver::new(Framebuffer::new(size));
+        let mut display = Display290BlackWhite::new();
+        display.clear(Color::Black);
+        driver.full_update(&display).unwrap();
+
+        display.clear(Color::White);
+        display.clear_dirty();
+        Pixel(Point::new(20, 30), Color::Black)
+            .draw(&mut display)
+            .unwrap();
+        Pixel(Point::new(20, 30), Color::White)
+            .draw(&mut display)
+            .unwrap();
+        driver.fast_update_dirty(&mut display).unwrap();
+
+        assert_eq!(display.dirty_area(), None);
+        assert_eq!(driver.target().pixel(16, 30), Rgb888::WHITE);
+        assert_eq!(driver.target().pixel(15, 30), Rgb888::BLACK);
+        assert_eq!(driver.target().pixel(20, 31), Rgb888::BLACK);
+
+        let viewport = display.viewport(Rectangle::new(Point::new(40, 50), Size::new(8, 2)));
+        driver.full_update_viewport(&viewport).unwrap();
+
+        assert_eq!(driver.target().pixel(40, 51), Rgb888::WHITE);
+        assert_eq!(driver.target().pixel(40, 52), Rgb888::BLACK);
+    }
+
+    #[test]
+    fn gray_and_full_partial_updates() {
+        let size = WeActStudio290SimulatedDriver::<Framebuffer>::size(DisplayRotation::Rotate0);
+        let mut driver = WeActStudio290SimulatedDriver::new(Framebuffer::new(size));
+        let mut display = Display290GrayLevel::new();
+        Pixel(Point::new(1, 1), GrayLevel::DarkGray)
+            .draw(&mut display)
+            .unwrap();
+
+        driver.full_update(&display).unwrap();
+        assert_eq!(driver.target().pixel(1, 1), GrayLevel::DarkGray.into());
+
+        let mut partial = DisplayBlackWhite::<8, 8, 8>::new();
+        partial.clear(Color::Black);
+        driver.full_partial_update(&partial, 8, 16).unwrap();
+        assert_eq!(driver.target().pixel(8, 16), Rgb888::BLACK);
+        assert_eq!(driver.target().pixel(1, 1), GrayLevel::DarkGray.into());
+    }
+}